@@ -0,0 +1,397 @@
+use alloy::primitives::{keccak256, B256};
+use alloy::transports::http::reqwest;
+use anyhow::{bail, Result};
+use axum::body::{to_bytes, Body};
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 请求体大小上限，超过时直接拒绝
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// introspection 结果的最长缓存时间
+const INTROSPECTION_CACHE_SECS: u64 = 60;
+
+/// introspection 缓存最多保存的令牌数，写入时先清理过期项，仍然满时淘汰最早过期的
+const INTROSPECTION_CACHE_CAPACITY: usize = 10_000;
+
+/// 单个令牌允许调用的工具
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolScope {
+    All,
+    Only(HashSet<String>),
+}
+
+impl ToolScope {
+    /// 解析以逗号或空格分隔的工具列表，`*` 表示全部工具
+    pub fn parse(list: &str) -> Self {
+        let tools: HashSet<String> = list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::trim)
+            .filter(|tool| !tool.is_empty())
+            .map(str::to_string)
+            .collect();
+        if tools.contains("*") {
+            ToolScope::All
+        } else {
+            ToolScope::Only(tools)
+        }
+    }
+
+    pub fn allows(&self, tool: &str) -> bool {
+        match self {
+            ToolScope::All => true,
+            ToolScope::Only(tools) => tools.contains(tool),
+        }
+    }
+}
+
+/// OAuth2 令牌自省（RFC 7662）端点配置
+#[derive(Debug, Clone)]
+pub struct IntrospectionConfig {
+    pub url: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// 认证配置：静态令牌表和可选的 introspection 端点
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    pub tokens: HashMap<String, ToolScope>,
+    pub introspection: Option<IntrospectionConfig>,
+}
+
+impl AuthConfig {
+    /// 解析 `token=tool,tool;token=*` 格式的静态令牌表
+    pub fn parse_tokens(spec: &str) -> Result<HashMap<String, ToolScope>> {
+        let mut tokens = HashMap::new();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((token, tools)) = entry.split_once('=') else {
+                bail!(
+                    "Invalid auth token entry '{}', expected token=tool,tool",
+                    entry
+                );
+            };
+            let token = token.trim();
+            if token.is_empty() {
+                bail!("Empty auth token in entry '{}'", entry);
+            }
+            tokens.insert(token.to_string(), ToolScope::parse(tools));
+        }
+        Ok(tokens)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty() && self.introspection.is_none()
+    }
+}
+
+/// introspection 端点的响应
+#[derive(Debug, Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    scope: Option<String>,
+    exp: Option<u64>,
+}
+
+/// 认证失败的原因
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    Forbidden(String),
+    Introspection(String),
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        match self {
+            AuthError::MissingToken => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                "Missing bearer token".to_string(),
+            )
+                .into_response(),
+            AuthError::InvalidToken => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer error=\"invalid_token\"")],
+                "Invalid or expired bearer token".to_string(),
+            )
+                .into_response(),
+            AuthError::Forbidden(tool) => (
+                StatusCode::FORBIDDEN,
+                format!("Token is not permitted to call tool '{}'", tool),
+            )
+                .into_response(),
+            AuthError::Introspection(e) => (
+                StatusCode::BAD_GATEWAY,
+                format!("Token introspection failed: {}", e),
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// 校验 bearer 令牌并解析其可调用的工具
+///
+/// 令牌只以哈希形式保存和比较，查找耗时与令牌内容的公共前缀无关，缓存中也不保留明文
+#[derive(Clone)]
+pub struct Authenticator {
+    tokens: Arc<HashMap<B256, ToolScope>>, // 静态令牌的哈希 -> 工具范围
+    introspection: Option<Arc<IntrospectionConfig>>,
+    client: reqwest::Client,
+    cache: Arc<Mutex<IntrospectionCache>>,
+}
+
+impl Authenticator {
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            tokens: Arc::new(
+                config
+                    .tokens
+                    .into_iter()
+                    .map(|(token, scope)| (keccak256(token.as_bytes()), scope))
+                    .collect(),
+            ),
+            introspection: config.introspection.map(Arc::new),
+            client: reqwest::Client::new(),
+            cache: Arc::new(Mutex::new(IntrospectionCache::new(
+                INTROSPECTION_CACHE_CAPACITY,
+            ))),
+        }
+    }
+
+    /// 先查静态令牌表，再查 introspection 缓存，最后请求 introspection 端点
+    pub async fn authorize(&self, token: &str) -> Result<ToolScope, AuthError> {
+        let key = keccak256(token.as_bytes());
+        if let Some(scope) = self.tokens.get(&key) {
+            return Ok(scope.clone());
+        }
+        let Some(introspection) = &self.introspection else {
+            return Err(AuthError::InvalidToken);
+        };
+
+        let now = unix_now();
+        if let Some(scope) = self.cache().get(&key, now) {
+            return Ok(scope);
+        }
+
+        let mut request = self
+            .client
+            .post(&introspection.url)
+            .timeout(Duration::from_secs(10))
+            .form(&[("token", token), ("token_type_hint", "access_token")]);
+        if let Some(client_id) = &introspection.client_id {
+            request = request.basic_auth(client_id, introspection.client_secret.as_ref());
+        }
+        let response: IntrospectionResponse = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::Introspection(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| AuthError::Introspection(e.to_string()))?;
+
+        // 只缓存有效的令牌，无效令牌每次都重新自省，避免随机令牌占满缓存
+        if !response.active || response.exp.is_some_and(|exp| exp <= now) {
+            return Err(AuthError::InvalidToken);
+        }
+        let scope = ToolScope::parse(response.scope.as_deref().unwrap_or_default());
+        let expires_at = response
+            .exp
+            .unwrap_or(u64::MAX)
+            .min(now + INTROSPECTION_CACHE_SECS);
+        self.cache().insert(key, scope.clone(), expires_at, now);
+        Ok(scope)
+    }
+
+    /// 持锁期间不会 panic，锁被毒化时缓存内容仍然完整，直接继续使用
+    fn cache(&self) -> MutexGuard<'_, IntrospectionCache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 有效令牌的 introspection 结果缓存
+struct IntrospectionCache {
+    entries: HashMap<B256, (ToolScope, u64)>, // 令牌哈希 -> (工具范围, 过期时间)
+    capacity: usize,
+}
+
+impl IntrospectionCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+        }
+    }
+
+    fn get(&self, key: &B256, now: u64) -> Option<ToolScope> {
+        self.entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(scope, _)| scope.clone())
+    }
+
+    fn insert(&mut self, key: B256, scope: ToolScope, expires_at: u64, now: u64) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.entries.retain(|_, (_, expires_at)| *expires_at > now);
+            if self.entries.len() >= self.capacity {
+                let earliest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, (_, expires_at))| *expires_at)
+                    .map(|(key, _)| *key);
+                if let Some(earliest) = earliest {
+                    self.entries.remove(&earliest);
+                }
+            }
+        }
+        self.entries.insert(key, (scope, expires_at));
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// 从 JSON-RPC 请求体（单个或批量）中取出 tools/call 调用的工具名
+pub fn called_tools(body: &[u8]) -> Vec<String> {
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return Vec::new();
+    };
+    let messages = match value {
+        serde_json::Value::Array(messages) => messages,
+        message => vec![message],
+    };
+    messages
+        .iter()
+        .filter(|message| message["method"] == "tools/call")
+        .map(|message| {
+            message["params"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect()
+}
+
+/// axum 中间件：要求 `Authorization: Bearer <token>`，并检查 tools/call 的工具是否在令牌范围内
+pub async fn require_auth(
+    State(auth): State<Authenticator>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| AuthError::MissingToken.into_response())?
+        .to_string();
+    let scope = auth
+        .authorize(&token)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    // 需要读取请求体判断调用的工具，检查后原样放回
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?;
+    if let Some(tool) = called_tools(&bytes)
+        .into_iter()
+        .find(|tool| !scope.allows(tool))
+    {
+        return Err(AuthError::Forbidden(tool).into_response());
+    }
+
+    Ok(next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_scope_parse() {
+        let scope = ToolScope::parse("get_balance, get_token_price");
+        assert!(scope.allows("get_balance"));
+        assert!(scope.allows("get_token_price"));
+        assert!(!scope.allows("swap_tokens"));
+        assert_eq!(ToolScope::parse("*"), ToolScope::All);
+    }
+
+    #[test]
+    fn test_parse_tokens() {
+        let tokens = AuthConfig::parse_tokens("reader=get_balance;admin=*").unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens["reader"].allows("get_balance"));
+        assert!(!tokens["reader"].allows("swap_tokens"));
+        assert!(tokens["admin"].allows("swap_tokens"));
+        assert!(AuthConfig::parse_tokens("missing_separator").is_err());
+    }
+
+    #[test]
+    fn test_introspection_cache_bounded() {
+        let key = |i: u8| keccak256([i]);
+        let mut cache = IntrospectionCache::new(2);
+        cache.insert(key(1), ToolScope::All, 100, 0);
+        cache.insert(key(2), ToolScope::All, 50, 0);
+        assert_eq!(cache.get(&key(2), 10), Some(ToolScope::All));
+        assert_eq!(cache.get(&key(2), 50), None);
+
+        // 已满时先清理过期项
+        cache.insert(key(3), ToolScope::All, 200, 60);
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(&key(1), 60).is_some());
+
+        // 没有过期项时淘汰最早过期的
+        cache.insert(key(4), ToolScope::All, 300, 60);
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(&key(1), 60).is_none());
+        assert!(cache.get(&key(3), 60).is_some());
+        assert!(cache.get(&key(4), 60).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_static_tokens() {
+        let auth = Authenticator::new(AuthConfig {
+            tokens: AuthConfig::parse_tokens("reader=get_balance").unwrap(),
+            introspection: None,
+        });
+        assert!(auth
+            .authorize("reader")
+            .await
+            .unwrap()
+            .allows("get_balance"));
+        assert!(matches!(
+            auth.authorize("reade").await,
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn test_called_tools() {
+        let single =
+            br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"swap_tokens"}}"#;
+        assert_eq!(called_tools(single), vec!["swap_tokens"]);
+
+        let batch = br#"[{"jsonrpc":"2.0","id":1,"method":"tools/list"},{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_balance"}}]"#;
+        assert_eq!(called_tools(batch), vec!["get_balance"]);
+
+        assert!(called_tools(b"not json").is_empty());
+    }
+}
@@ -0,0 +1,546 @@
+use crate::auth::{AuthConfig, IntrospectionConfig, ToolScope};
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::signers::local::PrivateKeySigner;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 未通过 --config 或 WALLET_MCP_CONFIG 指定时尝试读取的配置文件
+pub const DEFAULT_CONFIG_PATH: &str = "wallet-mcp.toml";
+pub const DEFAULT_NETWORK: &str = "mainnet";
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// 启动时检查 RPC 的超时时间
+const RPC_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 配置文件的顶层结构
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub default_network: Option<String>,
+    pub default_account: Option<String>,
+    #[serde(default)]
+    pub networks: HashMap<String, NetworkConfig>,
+    #[serde(default)]
+    pub accounts: HashMap<String, AccountConfig>,
+    #[serde(default)]
+    pub policy: PolicyConfig,
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub tokens: TokensConfig,
+    #[serde(default)]
+    pub auth: AuthSection,
+}
+
+/// 单个网络：RPC、链 ID、区块浏览器以及可选的合约地址覆盖
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>, // rpc_url 失败或限流时切换到的备用节点
+    pub quorum: Option<usize>, // 余额和报价需要多少个节点结果一致，未配置时只用一个节点
+    pub chain_id: Option<u64>, // 未配置时启动时从 RPC 读取
+    pub native_symbol: Option<String>, // 非内置链的原生代币符号，默认 ETH
+    pub explorer: Option<String>,
+    #[serde(default)]
+    pub contracts: ContractsConfig,
+}
+
+/// 网络上的合约地址，未配置的使用该链内置的地址
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractsConfig {
+    pub weth: Option<Address>,
+    pub usdc: Option<Address>,
+    pub usdt: Option<Address>,
+    pub dai: Option<Address>,
+    pub wbtc: Option<Address>,
+    pub quoter_v2: Option<Address>,
+    pub swap_router: Option<Address>,
+    pub v3_factory: Option<Address>,
+    pub v2_router: Option<Address>,
+    pub feed_registry: Option<Address>,
+    pub multicall3: Option<Address>,
+    pub ens_registry: Option<Address>,
+}
+
+/// 签名账户：直接配置私钥，或从指定的环境变量读取
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountConfig {
+    pub private_key: Option<String>,
+    pub private_key_env: Option<String>,
+}
+
+/// 交易策略
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PolicyConfig {
+    pub allow_broadcast: bool, // false 时 swap_tokens 和 transfer 只能 dry_run
+    pub max_slippage_bps: Option<u32>, // slippage_pct 的上限
+    pub max_price_deviation_bps: Option<u32>, // 请求未指定时广播前默认做价格共识检查
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            allow_broadcast: true,
+            max_slippage_bps: None,
+            max_price_deviation_bps: None,
+        }
+    }
+}
+
+/// RPC 连接参数，所有网络共用
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct RpcConfig {
+    pub timeout_secs: u64,               // 单个请求的超时时间
+    pub max_retries: u32,                // 遇到 429/503 时的最大重试次数
+    pub initial_backoff_ms: u64,         // 首次重试前的等待时间
+    pub compute_units_per_second: u64,   // 节点服务商的 CU 配额，用于估算退避时间
+    pub health_check_interval_secs: u64, // 多节点时健康检查的间隔，0 表示不检查
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            max_retries: 5,
+            initial_backoff_ms: 500,
+            compute_units_per_second: 330,
+            health_check_interval_secs: 30,
+        }
+    }
+}
+
+/// 通过 Transfer 日志发现钱包持有的代币
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct DiscoveryConfig {
+    pub cache_dir: PathBuf,   // 每个钱包已扫描到的区块和发现的代币
+    pub block_range: u64,     // 每次 eth_getLogs 的初始区块数，节点拒绝时自动减半
+    pub max_block_range: u64, // 连续成功时区块数逐步放大的上限
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: PathBuf::from(".wallet-mcp-cache"),
+            block_range: 10_000,
+            max_block_range: 1_000_000,
+        }
+    }
+}
+
+/// 按符号或名称解析代币时额外加载的 token list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct TokensConfig {
+    pub lists: Vec<PathBuf>, // Uniswap token list 格式的 JSON 文件，靠前的优先
+}
+
+/// HTTP 传输的认证设置，环境变量 MCP_AUTH_* 会与之合并
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthSection {
+    #[serde(default)]
+    pub tokens: HashMap<String, String>, // 令牌 -> 允许的工具（逗号分隔，* 表示全部）
+    pub introspection_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+impl Config {
+    /// 依次加载 .env、配置文件和环境变量覆盖，并做静态校验
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        dotenvy::dotenv().ok();
+
+        let path = path
+            .map(PathBuf::from)
+            .or_else(|| env::var("WALLET_MCP_CONFIG").ok().map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+        config.apply_overrides(|name| env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::from_toml(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// 环境变量优先于配置文件：ETH_RPC_URL、ETH_CHAIN_ID 作用于默认网络，PRIVATE_KEY 作用于默认账户
+    pub fn apply_overrides(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let network = self.default_network_name().to_string();
+        if let Some(rpc_url) = var("ETH_RPC_URL") {
+            self.networks.entry(network.clone()).or_default().rpc_url = rpc_url;
+        }
+        if let Some(chain_id) = var("ETH_CHAIN_ID") {
+            let chain_id = chain_id
+                .parse()
+                .map_err(|e| anyhow!("Invalid ETH_CHAIN_ID '{}': {}", chain_id, e))?;
+            self.networks.entry(network).or_default().chain_id = Some(chain_id);
+        }
+
+        let account = self.default_account_name().to_string();
+        if let Some(private_key) = var("PRIVATE_KEY") {
+            self.accounts.entry(account).or_default().private_key = Some(private_key);
+        }
+
+        if let Some(tokens) = var("MCP_AUTH_TOKENS") {
+            for (token, scope) in AuthConfig::parse_tokens(&tokens)? {
+                let tools = match scope {
+                    ToolScope::All => "*".to_string(),
+                    ToolScope::Only(tools) => tools.into_iter().collect::<Vec<_>>().join(","),
+                };
+                self.auth.tokens.insert(token, tools);
+            }
+        }
+        if let Some(url) = var("MCP_AUTH_INTROSPECTION_URL") {
+            self.auth.introspection_url = Some(url);
+        }
+        if let Some(client_id) = var("MCP_AUTH_CLIENT_ID") {
+            self.auth.client_id = Some(client_id);
+        }
+        if let Some(client_secret) = var("MCP_AUTH_CLIENT_SECRET") {
+            self.auth.client_secret = Some(client_secret);
+        }
+        Ok(())
+    }
+
+    /// 不访问网络的校验：默认网络存在、RPC 地址非空、配置了账户时私钥可以解析
+    ///
+    /// 允许广播时必须配置账户；`allow_broadcast = false` 时可以不配置账户，只提供查询工具
+    pub fn validate(&self) -> Result<()> {
+        let network = self.default_network_name();
+        if !self.networks.contains_key(network) {
+            bail!(
+                "Default network '{}' is not configured, set ETH_RPC_URL or add [networks.{}] to the config file",
+                network,
+                network
+            );
+        }
+        for (name, network) in &self.networks {
+            if network.rpc_urls().iter().any(|url| url.trim().is_empty()) {
+                bail!("Network '{}' has an empty rpc_url", name);
+            }
+            if let Some(quorum) = network.quorum {
+                let endpoints = network.rpc_urls().len();
+                if quorum == 0 || quorum > endpoints {
+                    bail!(
+                        "Network '{}': quorum must be between 1 and the number of RPC URLs ({}), got {}",
+                        name,
+                        endpoints,
+                        quorum
+                    );
+                }
+            }
+        }
+        let account = self.default_account_name();
+        let Some(private_key) = self.signing_key()? else {
+            if self.policy.allow_broadcast {
+                bail!(
+                    "Account '{}' is not configured, set PRIVATE_KEY or add [accounts.{}] to the config file, or set allow_broadcast = false in [policy] to run without an account",
+                    account,
+                    account
+                );
+            }
+            tracing::info!("No account configured, swap_tokens and transfer are unavailable");
+            return Ok(());
+        };
+        let signer: PrivateKeySigner = private_key
+            .parse()
+            .map_err(|e| anyhow!("Invalid private key for account '{}': {}", account, e))?;
+        tracing::info!("Using account {}", signer.address());
+        Ok(())
+    }
+
+    /// 启动时检查每个网络的 RPC 可达且链 ID 与配置一致，未配置链 ID 时以 RPC 返回值为准
+    ///
+    /// 有备用节点时只要求至少一个节点可达，不可达的节点只记录警告；任何节点链 ID 不一致都直接报错
+    pub async fn check_networks(&mut self) -> Result<()> {
+        for (name, network) in self.networks.iter_mut() {
+            let mut reachable = 0;
+            let mut last_error = None;
+            for rpc_url in network.rpc_urls() {
+                let chain_id = match probe_chain_id(&rpc_url).await {
+                    Ok(chain_id) => chain_id,
+                    Err(e) => {
+                        tracing::warn!("Network '{}': {:#}", name, e);
+                        last_error = Some(e);
+                        continue;
+                    }
+                };
+                match network.chain_id {
+                    Some(expected) if expected != chain_id => bail!(
+                        "Network '{}': chain id mismatch, config says {} but RPC {} reports {}",
+                        name,
+                        expected,
+                        rpc_url,
+                        chain_id
+                    ),
+                    Some(_) => {}
+                    None => network.chain_id = Some(chain_id),
+                }
+                reachable += 1;
+            }
+            if let Some(e) = last_error.filter(|_| reachable == 0) {
+                return Err(e.context(format!("Network '{}' has no reachable RPC", name)));
+            }
+            if let Some(quorum) = network.quorum.filter(|quorum| reachable < *quorum) {
+                tracing::warn!(
+                    "Network '{}': only {} RPC endpoints reachable, reads need {} to agree",
+                    name,
+                    reachable,
+                    quorum
+                );
+            }
+            tracing::info!(
+                "Network '{}' ready (chain id {}, {} of {} RPC endpoints reachable)",
+                name,
+                network.chain_id.unwrap_or_default(),
+                reachable,
+                network.rpc_urls().len()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn default_network_name(&self) -> &str {
+        self.default_network.as_deref().unwrap_or(DEFAULT_NETWORK)
+    }
+
+    pub fn default_account_name(&self) -> &str {
+        self.default_account.as_deref().unwrap_or(DEFAULT_ACCOUNT)
+    }
+
+    pub fn default_network(&self) -> Result<&NetworkConfig> {
+        let name = self.default_network_name();
+        self.networks
+            .get(name)
+            .ok_or_else(|| anyhow!("Network '{}' is not configured", name))
+    }
+
+    /// 默认账户的私钥
+    pub fn private_key(&self) -> Result<String> {
+        let name = self.default_account_name();
+        let account = self.accounts.get(name).ok_or_else(|| {
+            anyhow!("Account '{}' is not configured, set PRIVATE_KEY or add [accounts.{}] to the config file", name, name)
+        })?;
+        match (&account.private_key, &account.private_key_env) {
+            (Some(private_key), _) => Ok(private_key.clone()),
+            (None, Some(var)) => env::var(var).map_err(|_| {
+                anyhow!(
+                    "Account '{}': environment variable {} is not set",
+                    name,
+                    var
+                )
+            }),
+            (None, None) => bail!(
+                "Account '{}' has neither private_key nor private_key_env",
+                name
+            ),
+        }
+    }
+
+    /// 默认账户的私钥，未配置默认账户时返回 None；账户已配置但私钥不可用时仍然报错
+    pub fn signing_key(&self) -> Result<Option<String>> {
+        if !self.accounts.contains_key(self.default_account_name()) {
+            return Ok(None);
+        }
+        self.private_key().map(Some)
+    }
+
+    /// 合并后的认证配置，未配置任何令牌或自省端点时返回 None
+    pub fn auth_config(&self) -> Option<AuthConfig> {
+        let config = AuthConfig {
+            tokens: self
+                .auth
+                .tokens
+                .iter()
+                .map(|(token, tools)| (token.clone(), ToolScope::parse(tools)))
+                .collect(),
+            introspection: self
+                .auth
+                .introspection_url
+                .clone()
+                .map(|url| IntrospectionConfig {
+                    url,
+                    client_id: self.auth.client_id.clone(),
+                    client_secret: self.auth.client_secret.clone(),
+                }),
+        };
+        (!config.is_empty()).then_some(config)
+    }
+}
+
+impl NetworkConfig {
+    /// 主节点在前，之后是备用节点
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(&self.rpc_url)
+            .chain(&self.fallback_rpc_urls)
+            .cloned()
+            .collect()
+    }
+}
+
+/// 读取单个 RPC 节点的链 ID
+async fn probe_chain_id(rpc_url: &str) -> Result<u64> {
+    let provider = ProviderBuilder::new()
+        .connect(rpc_url)
+        .await
+        .with_context(|| format!("failed to connect to {}", rpc_url))?;
+    tokio::time::timeout(RPC_CHECK_TIMEOUT, provider.get_chain_id())
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "RPC {} did not respond within {}s",
+                rpc_url,
+                RPC_CHECK_TIMEOUT.as_secs()
+            )
+        })?
+        .with_context(|| format!("RPC {} is unreachable", rpc_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    const EXAMPLE: &str = r#"
+default_network = "mainnet"
+
+[networks.mainnet]
+rpc_url = "http://localhost:8545"
+chain_id = 1
+explorer = "https://etherscan.io"
+
+[networks.mainnet.contracts]
+weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+
+[accounts.default]
+private_key_env = "WALLET_MCP_TEST_UNSET_KEY"
+
+[policy]
+allow_broadcast = false
+max_slippage_bps = 300
+
+[auth.tokens]
+reader = "get_balance,get_token_price"
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::from_toml(EXAMPLE).unwrap();
+        let network = config.default_network().unwrap();
+        assert_eq!(network.chain_id, Some(1));
+        assert!(network.contracts.weth.is_some());
+        assert!(!config.policy.allow_broadcast);
+        assert_eq!(config.policy.max_slippage_bps, Some(300));
+
+        let auth = config.auth_config().unwrap();
+        assert!(auth.tokens["reader"].allows("get_balance"));
+        assert!(!auth.tokens["reader"].allows("swap_tokens"));
+    }
+
+    #[test]
+    fn test_unknown_field_rejected() {
+        assert!(Config::from_toml("[networks.mainnet]\nrpc = \"http://x\"").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = Config::from_toml(EXAMPLE).unwrap();
+        config
+            .apply_overrides(|name| match name {
+                "ETH_RPC_URL" => Some("http://override:8545".to_string()),
+                "PRIVATE_KEY" => Some(TEST_KEY.to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            config.default_network().unwrap().rpc_url,
+            "http://override:8545"
+        );
+        assert_eq!(config.private_key().unwrap(), TEST_KEY);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_fallback_urls_and_quorum() {
+        let mut config = Config::from_toml(
+            r#"
+[networks.mainnet]
+rpc_url = "http://a:8545"
+fallback_rpc_urls = ["http://b:8545", "http://c:8545"]
+quorum = 2
+"#,
+        )
+        .unwrap();
+        config
+            .apply_overrides(|name| (name == "PRIVATE_KEY").then(|| TEST_KEY.to_string()))
+            .unwrap();
+        assert_eq!(
+            config.default_network().unwrap().rpc_urls(),
+            vec!["http://a:8545", "http://b:8545", "http://c:8545"]
+        );
+        assert!(config.validate().is_ok());
+
+        config.networks.get_mut("mainnet").unwrap().quorum = Some(4);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_without_network() {
+        let mut config = Config::default();
+        config
+            .apply_overrides(|name| (name == "PRIVATE_KEY").then(|| TEST_KEY.to_string()))
+            .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_account_optional_without_broadcast() {
+        let read_only = r#"
+[networks.mainnet]
+rpc_url = "http://localhost:8545"
+
+[policy]
+allow_broadcast = false
+"#;
+        let config = Config::from_toml(read_only).unwrap();
+        assert_eq!(config.signing_key().unwrap(), None);
+        assert!(config.validate().is_ok());
+
+        // 允许广播时必须配置账户
+        let config =
+            Config::from_toml("[networks.mainnet]\nrpc_url = \"http://localhost:8545\"").unwrap();
+        assert!(config.validate().is_err());
+
+        // 配置了账户但私钥不可用时不会被当作没有账户
+        let config = Config::from_toml(EXAMPLE).unwrap();
+        assert!(config.signing_key().is_err());
+        assert!(config.validate().is_err());
+    }
+}
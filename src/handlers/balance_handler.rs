@@ -1,19 +1,41 @@
-use crate::models::IERC20;
-use crate::models::{GetBalanceRequest, GetBalanceResponse, MetaData};
-use alloy::primitives::Address;
-use alloy::providers::{Provider, ProviderBuilder};
+use crate::models::{
+    BalanceCell, DiscoverTokensRequest, DiscoverTokensResponse, GetBalanceRequest,
+    GetBalanceResponse, GetBalancesRequest, GetBalancesResponse, MetaData, WalletBalances,
+};
+use crate::models::{IMulticall3, IERC20};
+use crate::services::block_resolver;
+use crate::services::token_discovery::{Discovered, TokenDiscovery};
+use crate::services::ChainContext;
+use alloy::eips::BlockId;
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::providers::Provider;
+use alloy::sol_types::SolCall;
+use futures::future::try_join_all;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::{model::*, ErrorData as McpError};
-use std::str::FromStr;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 单次 aggregate3 的最大调用数，避免超出节点 eth_call 的 gas 上限，超过时分批调用
+const MAX_MULTICALL_CALLS: usize = 1000;
 
 #[derive(Clone)]
 pub struct BalanceHandler {
-    rpc_url: String,
+    chain: Arc<ChainContext>,
+    discovery: TokenDiscovery,
 }
 
 impl BalanceHandler {
-    pub fn new(rpc_url: String) -> Self {
-        Self { rpc_url }
+    pub fn new(chain: Arc<ChainContext>) -> Self {
+        Self {
+            chain,
+            discovery: TokenDiscovery::default(),
+        }
+    }
+
+    pub fn with_discovery(mut self, discovery: TokenDiscovery) -> Self {
+        self.discovery = discovery;
+        self
     }
 
     pub async fn handle_get_balance(
@@ -21,27 +43,37 @@ impl BalanceHandler {
         Parameters(GetBalanceRequest {
             wallet_address,
             token_address,
+            block,
+            chain: _,
         }): Parameters<GetBalanceRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let address = Address::from_str(wallet_address.as_str()).map_err(|e| {
-            McpError::invalid_params(format!("Invalid wallet address: {}", e), None)
-        })?;
-        let provider = ProviderBuilder::new()
-            .connect(self.rpc_url.as_str())
-            .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to connect to RPC: {}", e), None)
-            })?;
+        let wallet = self
+            .chain
+            .resolve_address(&wallet_address, "wallet")
+            .await?;
+        let address = wallet.address;
+        let provider = self.chain.provider.clone();
+        let block_number = match &block {
+            Some(block) => Some(block_resolver::resolve_block(&provider, block).await?),
+            None => None,
+        };
+        let block_id = block_number.map_or(BlockId::latest(), BlockId::number);
+        // 指定区块时识别非归档节点的错误
+        let balance_error = |context: &str, error: String| match block_number {
+            Some(number) => block_resolver::historical_error(context, number, error),
+            None => McpError::internal_error(format!("{}: {}", context, error), None),
+        };
 
         if let Some(token_addr) = token_address {
             // 查询 ERC20 token 余额
-            let token_address = Address::from_str(token_addr.as_str()).map_err(|e| {
-                McpError::invalid_params(format!("Invalid token address: {}", e), None)
-            })?;
+            let token_address = self.chain.resolve_token(&token_addr)?;
             let erc20 = IERC20::new(token_address, provider);
-            let balance = erc20.balanceOf(address).call().await.map_err(|e| {
-                McpError::internal_error(format!("Failed to get token balance: {}", e), None)
-            })?;
+            let balance = erc20
+                .balanceOf(address)
+                .call()
+                .block(block_id)
+                .await
+                .map_err(|e| balance_error("Failed to get token balance", e.to_string()))?;
             let decimals = erc20.decimals().call().await.map_err(|e| {
                 McpError::internal_error(format!("Failed to get token decimals: {}", e), None)
             })?;
@@ -55,24 +87,324 @@ impl BalanceHandler {
                         decimals,
                         symbol,
                     },
+                    wallet: address.to_string(),
+                    ens_name: wallet.ens_name,
+                    block_number,
                 }
                 .to_string(),
             )]))
         } else {
-            // 查询 ETH 余额
-            let balance_wei = provider.get_balance(address).await.map_err(|e| {
-                McpError::internal_error(format!("Failed to get ETH balance: {}", e), None)
-            })?;
+            // 查询原生代币余额
+            let balance_wei = provider
+                .get_balance(address)
+                .block_id(block_id)
+                .await
+                .map_err(|e| balance_error("Failed to get native balance", e.to_string()))?;
             Ok(CallToolResult::success(vec![Content::text(
                 GetBalanceResponse {
                     price: MetaData {
                         value: balance_wei,
                         decimals: 18,
-                        symbol: "ETH".to_string(),
+                        symbol: self.chain.addresses.native_symbol.to_string(),
                     },
+                    wallet: address.to_string(),
+                    ens_name: wallet.ens_name,
+                    block_number,
                 }
                 .to_string(),
             )]))
         }
     }
+
+    /// 多个钱包 × 多个代币的余额，所有 balanceOf/decimals/symbol/getEthBalance 合并为一次 aggregate3
+    pub async fn handle_get_balances(
+        &self,
+        Parameters(GetBalancesRequest {
+            wallet_addresses,
+            token_addresses,
+            include_native,
+            discover,
+            chain: _,
+        }): Parameters<GetBalancesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let resolved = self
+            .chain
+            .resolve_addresses(&wallet_addresses, "wallet")
+            .await?;
+        let wallets: Vec<Address> = resolved.iter().map(|wallet| wallet.address).collect();
+        let mut tokens = self
+            .chain
+            .resolve_tokens(&token_addresses.unwrap_or_default())?;
+        if discover.unwrap_or(false) {
+            for wallet in &wallets {
+                tokens.extend(self.discover_tokens(*wallet, None).await?.tokens);
+            }
+        }
+        let mut response = self
+            .balances(&wallets, tokens, include_native.unwrap_or(true))
+            .await?;
+        for (row, wallet) in response.rows.iter_mut().zip(resolved) {
+            row.ens_name = wallet.ens_name;
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+
+    pub async fn handle_discover_tokens(
+        &self,
+        Parameters(DiscoverTokensRequest {
+            wallet_address,
+            from_block,
+            chain: _,
+        }): Parameters<DiscoverTokensRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let wallet = self
+            .chain
+            .resolve_address(&wallet_address, "wallet")
+            .await?;
+        let discovered = self.discover_tokens(wallet.address, from_block).await?;
+        Ok(CallToolResult::success(vec![Content::text(
+            DiscoverTokensResponse {
+                wallet: wallet.address.to_string(),
+                ens_name: wallet.ens_name,
+                tokens: discovered
+                    .tokens
+                    .iter()
+                    .map(|token| token.to_string())
+                    .collect(),
+                from_block: discovered.from_block,
+                to_block: discovered.to_block,
+                requests: discovered.requests,
+            }
+            .to_string(),
+        )]))
+    }
+
+    /// 扫描钱包的 Transfer 日志，增量结果缓存在本地
+    pub async fn discover_tokens(
+        &self,
+        wallet: Address,
+        from_block: Option<u64>,
+    ) -> Result<Discovered, McpError> {
+        self.discovery
+            .discover(
+                &self.chain.provider,
+                self.chain.chain_id(),
+                wallet,
+                from_block,
+            )
+            .await
+    }
+
+    /// 多个钱包 × 多个代币的余额表，单个调用失败只影响对应单元格
+    pub async fn balances(
+        &self,
+        wallets: &[Address],
+        mut tokens: Vec<Address>,
+        include_native: bool,
+    ) -> Result<GetBalancesResponse, McpError> {
+        let mut seen = HashSet::new();
+        tokens.retain(|token| seen.insert(*token));
+        if wallets.is_empty() {
+            return Err(McpError::invalid_params(
+                "wallet_addresses must not be empty".to_string(),
+                None,
+            ));
+        }
+        if tokens.is_empty() && !include_native {
+            return Err(McpError::invalid_params(
+                "No tokens to query: provide token_addresses or set include_native".to_string(),
+                None,
+            ));
+        }
+
+        // 调用顺序：每个代币的 decimals/symbol，然后每个钱包的原生余额和各代币 balanceOf
+        let multicall = self.chain.addresses.multicall3;
+        let mut calls = Vec::new();
+        for token in &tokens {
+            calls.push(call3(*token, IERC20::decimalsCall {}.abi_encode()));
+            calls.push(call3(*token, IERC20::symbolCall {}.abi_encode()));
+        }
+        for wallet in wallets {
+            if include_native {
+                calls.push(call3(
+                    multicall,
+                    IMulticall3::getEthBalanceCall { addr: *wallet }.abi_encode(),
+                ));
+            }
+            for token in &tokens {
+                calls.push(call3(
+                    *token,
+                    IERC20::balanceOfCall { account: *wallet }.abi_encode(),
+                ));
+            }
+        }
+
+        // 调用过多时分批 aggregate3，所有批次固定在同一个区块上，结果仍来自同一个区块
+        let block_number = self.chain.provider.get_block_number().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get block number: {}", e), None)
+        })?;
+        let multicall3 = IMulticall3::new(multicall, self.chain.provider.clone());
+        let batches = try_join_all(calls.chunks(MAX_MULTICALL_CALLS).map(|batch| {
+            let call = multicall3
+                .aggregate3(batch.to_vec())
+                .block(BlockId::number(block_number));
+            async move {
+                call.call().await.map_err(|e| {
+                    McpError::internal_error(format!("Multicall3 aggregate3 failed: {}", e), None)
+                })
+            }
+        }))
+        .await?;
+        let mut results = batches.into_iter().flatten();
+        let block_number = Some(block_number);
+        let metadata: Vec<(Result<u8, String>, String)> = tokens
+            .iter()
+            .map(|token| {
+                let decimals = decode::<IERC20::decimalsCall>(results.next())
+                    .map_err(|e| format!("decimals {}", e));
+                let symbol = decode_symbol(results.next()).unwrap_or_else(|| token.to_string());
+                (decimals, symbol)
+            })
+            .collect();
+
+        let native_symbol = self.chain.addresses.native_symbol.to_string();
+        let mut columns = Vec::new();
+        if include_native {
+            columns.push(native_symbol.clone());
+        }
+        columns.extend(metadata.iter().map(|(_, symbol)| symbol.clone()));
+
+        let rows = wallets
+            .iter()
+            .map(|wallet| {
+                let mut balances = Vec::with_capacity(columns.len());
+                if include_native {
+                    let balance = decode::<IMulticall3::getEthBalanceCall>(results.next());
+                    balances.push(balance_cell(None, balance, Ok(18), &native_symbol));
+                }
+                for (token, (decimals, symbol)) in tokens.iter().zip(&metadata) {
+                    let balance = decode::<IERC20::balanceOfCall>(results.next());
+                    balances.push(balance_cell(
+                        Some(*token),
+                        balance,
+                        decimals.clone(),
+                        symbol,
+                    ));
+                }
+                WalletBalances {
+                    wallet: wallet.to_string(),
+                    ens_name: None,
+                    balances,
+                }
+            })
+            .collect();
+
+        Ok(GetBalancesResponse {
+            block_number,
+            columns,
+            rows,
+        })
+    }
+}
+
+fn call3(target: Address, call_data: Vec<u8>) -> IMulticall3::Call3 {
+    IMulticall3::Call3 {
+        target,
+        allowFailure: true,
+        callData: Bytes::from(call_data),
+    }
+}
+
+/// 解码 aggregate3 中单个调用的返回值，失败或返回数据无效时返回错误说明
+fn decode<C: SolCall>(result: Option<IMulticall3::Result>) -> Result<C::Return, String> {
+    match result {
+        Some(result) if result.success => C::abi_decode_returns(&result.returnData)
+            .map_err(|_| "returned invalid data".to_string()),
+        Some(_) => Err("call reverted".to_string()),
+        None => Err("missing result".to_string()),
+    }
+}
+
+/// symbol() 可能返回 string，也可能是早期代币（如 MKR）使用的 bytes32
+fn decode_symbol(result: Option<IMulticall3::Result>) -> Option<String> {
+    let result = result.filter(|result| result.success)?;
+    if let Ok(symbol) = IERC20::symbolCall::abi_decode_returns(&result.returnData) {
+        return Some(symbol);
+    }
+    if result.returnData.len() != 32 {
+        return None;
+    }
+    let symbol: Vec<u8> = result
+        .returnData
+        .iter()
+        .copied()
+        .take_while(|b| *b != 0)
+        .collect();
+    String::from_utf8(symbol)
+        .ok()
+        .filter(|symbol| !symbol.is_empty())
+}
+
+fn balance_cell(
+    token: Option<Address>,
+    balance: Result<U256, String>,
+    decimals: Result<u8, String>,
+    symbol: &str,
+) -> BalanceCell {
+    match (balance, decimals) {
+        (Ok(value), Ok(decimals)) => BalanceCell {
+            token,
+            balance: Some(MetaData {
+                value,
+                decimals,
+                symbol: symbol.to_string(),
+            }),
+            error: None,
+        },
+        (Err(e), _) => BalanceCell {
+            token,
+            balance: None,
+            error: Some(format!("balance {}", e)),
+        },
+        (_, Err(e)) => BalanceCell {
+            token,
+            balance: None,
+            error: Some(e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, data: Vec<u8>) -> Option<IMulticall3::Result> {
+        Some(IMulticall3::Result {
+            success,
+            returnData: Bytes::from(data),
+        })
+    }
+
+    #[test]
+    fn test_decode_tolerates_failures() {
+        let ok = IERC20::balanceOfCall::abi_encode_returns(&U256::from(42));
+        assert_eq!(
+            decode::<IERC20::balanceOfCall>(result(true, ok)),
+            Ok(U256::from(42))
+        );
+        assert!(decode::<IERC20::balanceOfCall>(result(false, vec![])).is_err());
+        assert!(decode::<IERC20::balanceOfCall>(result(true, vec![])).is_err());
+    }
+
+    #[test]
+    fn test_decode_bytes32_symbol() {
+        let mut mkr = b"MKR".to_vec();
+        mkr.resize(32, 0);
+        assert_eq!(decode_symbol(result(true, mkr)), Some("MKR".to_string()));
+        let usdc = IERC20::symbolCall::abi_encode_returns(&"USDC".to_string());
+        assert_eq!(decode_symbol(result(true, usdc)), Some("USDC".to_string()));
+        assert_eq!(decode_symbol(result(false, vec![])), None);
+    }
 }
@@ -1,7 +1,13 @@
 pub mod balance_handler;
+pub mod portfolio_handler;
 pub mod price_handler;
 pub mod swap_handler;
+pub mod transaction_handler;
+pub mod transfer_handler;
 
 pub use balance_handler::BalanceHandler;
+pub use portfolio_handler::PortfolioHandler;
 pub use price_handler::PriceHandler;
-pub use swap_handler::SwapHandler;
\ No newline at end of file
+pub use swap_handler::SwapHandler;
+pub use transaction_handler::TransactionHandler;
+pub use transfer_handler::TransferHandler;
@@ -0,0 +1,193 @@
+use crate::handlers::{BalanceHandler, PriceHandler};
+use crate::models::{
+    GetPortfolioRequest, GetPortfolioResponse, MetaData, PortfolioHolding, USD_QUOTE,
+};
+use crate::services::consensus::CONSENSUS_DECIMALS;
+use crate::services::portfolio::{self, DEFAULT_MIN_VALUE_USD};
+use crate::services::token_discovery::TokenDiscovery;
+use crate::services::ChainContext;
+use alloy::primitives::{Address, U256};
+use futures::stream::{self, StreamExt};
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::{model::*, ErrorData as McpError};
+use std::sync::Arc;
+
+/// 同时定价的持仓数上限
+const PRICING_CONCURRENCY: usize = 8;
+
+#[derive(Clone)]
+pub struct PortfolioHandler {
+    chain: Arc<ChainContext>,
+    discovery: TokenDiscovery,
+}
+
+impl PortfolioHandler {
+    pub fn new(chain: Arc<ChainContext>) -> Self {
+        Self {
+            chain,
+            discovery: TokenDiscovery::default(),
+        }
+    }
+
+    pub fn with_discovery(mut self, discovery: TokenDiscovery) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    pub async fn handle_get_portfolio(
+        &self,
+        Parameters(GetPortfolioRequest {
+            wallet_address,
+            token_addresses,
+            min_value_usd,
+            discover,
+            chain: _,
+        }): Parameters<GetPortfolioRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let resolved = self
+            .chain
+            .resolve_address(&wallet_address, "wallet")
+            .await?;
+        let wallet = resolved.address;
+        let mut tokens = match token_addresses {
+            Some(tokens) => self.chain.resolve_tokens(&tokens)?,
+            None => self
+                .chain
+                .addresses
+                .known_tokens()
+                .into_iter()
+                .map(|(_, address)| address)
+                .collect(),
+        };
+        if discover.unwrap_or(false) {
+            tokens.extend(
+                self.balance_handler()
+                    .discover_tokens(wallet, None)
+                    .await?
+                    .tokens,
+            );
+        }
+        let min_value = portfolio::usd_amount(min_value_usd.unwrap_or(DEFAULT_MIN_VALUE_USD));
+
+        let mut response = self.portfolio(wallet, tokens, min_value).await?;
+        response.ens_name = resolved.ens_name;
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+
+    fn balance_handler(&self) -> BalanceHandler {
+        BalanceHandler::new(self.chain.clone()).with_discovery(self.discovery.clone())
+    }
+
+    /// 一次 Multicall 读取余额，再并发为每个非零持仓定价
+    pub async fn portfolio(
+        &self,
+        wallet: Address,
+        tokens: Vec<Address>,
+        min_value: U256,
+    ) -> Result<GetPortfolioResponse, McpError> {
+        let balances = self
+            .balance_handler()
+            .balances(&[wallet], tokens, true)
+            .await?;
+        // 余额读取失败的代币（例如不是 ERC20 合约）不计入持仓，可通过 get_balances 查看原因
+        let held: Vec<(Option<Address>, MetaData)> = balances
+            .rows
+            .into_iter()
+            .flat_map(|row| row.balances)
+            .filter_map(|cell| {
+                let balance = cell.balance.filter(|balance| !balance.value.is_zero())?;
+                Some((cell.token, balance))
+            })
+            .collect();
+
+        // 原生代币按包装代币定价，限制同时定价的代币数，避免发现的大量代币同时打满 RPC
+        let price_handler = PriceHandler::new(self.chain.clone());
+        let wrapped_native = self.chain.addresses.wrapped_native;
+        let prices: Vec<_> = stream::iter(held.iter().map(|(token, _)| {
+            let price_token = token.unwrap_or(wrapped_native);
+            let price_handler = price_handler.clone();
+            async move { price_handler.usd_price(price_token).await }
+        }))
+        .buffered(PRICING_CONCURRENCY)
+        .collect()
+        .await;
+
+        let mut holdings = Vec::new();
+        let mut dust_skipped = 0;
+        let mut total = U256::ZERO;
+        for ((token, amount), price) in held.into_iter().zip(prices) {
+            // 无法定价且不在已知代币表中的多为空投垃圾代币，与粉尘一样跳过
+            let known = token.is_none_or(|token| self.chain.is_known_token(token));
+            if price.is_err() && !known {
+                dust_skipped += 1;
+                continue;
+            }
+            let token = match token {
+                Some(token) => token.to_string(),
+                None => self.chain.addresses.native_symbol.to_string(),
+            };
+            let holding = match price {
+                Ok(price) => {
+                    let value = portfolio::value_usd(
+                        amount.value,
+                        amount.decimals,
+                        price.value,
+                        price.decimals,
+                    );
+                    if value < min_value {
+                        dust_skipped += 1;
+                        continue;
+                    }
+                    total += value;
+                    PortfolioHolding {
+                        token,
+                        amount,
+                        price_usd: Some(price),
+                        value_usd: Some(usd(value)),
+                        share_bps: None,
+                        error: None,
+                    }
+                }
+                Err(e) => PortfolioHolding {
+                    token,
+                    amount,
+                    price_usd: None,
+                    value_usd: None,
+                    share_bps: None,
+                    error: Some(e.message.to_string()),
+                },
+            };
+            holdings.push(holding);
+        }
+
+        for holding in &mut holdings {
+            holding.share_bps = holding
+                .value_usd
+                .as_ref()
+                .map(|value| portfolio::share_bps(value.value, total));
+        }
+        holdings.sort_by(|a, b| {
+            let value = |holding: &PortfolioHolding| holding.value_usd.as_ref().map(|v| v.value);
+            value(b).cmp(&value(a))
+        });
+
+        Ok(GetPortfolioResponse {
+            wallet: wallet.to_string(),
+            ens_name: None,
+            block_number: balances.block_number,
+            holdings,
+            total_value_usd: usd(total),
+            dust_skipped,
+        })
+    }
+}
+
+fn usd(value: U256) -> MetaData {
+    MetaData {
+        value,
+        decimals: CONSENSUS_DECIMALS,
+        symbol: USD_QUOTE.to_string(),
+    }
+}
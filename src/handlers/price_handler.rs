@@ -1,67 +1,441 @@
-use crate::models::{QUOTER_ADDRESS, USDT_ADDRESS};
-use crate::models::{IQuoter, IERC20};
-use crate::models::{GetTokenPriceRequest, GetTokenPriceResponse, MetaData};
-use alloy::primitives::{Address, U160, U256};
-use alloy::primitives::aliases::U24;
-use alloy::providers::ProviderBuilder;
+use crate::models::{ChainAddresses, IERC20};
+use crate::models::{GetPriceConsensusRequest, GetPriceConsensusResponse, SourcePrice};
+use crate::models::{GetTokenPriceRequest, GetTokenPriceResponse, MetaData, PriceSource};
+use crate::models::{NATIVE_ETH, USD_QUOTE};
+use crate::services::chainlink;
+use crate::services::consensus::{self, CONSENSUS_DECIMALS, DEFAULT_MAX_DEVIATION_BPS};
+use crate::services::fee_tier;
+use crate::services::pool_price::{self, DEFAULT_TWAP_WINDOW_SECS};
+use crate::services::routing::{self, Route};
+use crate::services::ChainContext;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::{model::*, ErrorData as McpError};
-use std::str::FromStr;
+use std::sync::Arc;
+
+/// 解析后的计价代币
+struct QuoteToken {
+    address: Address,
+    symbol: Option<String>, // 覆盖链上符号，例如原生 ETH
+    is_usd: bool,
+}
 
 #[derive(Clone)]
 pub struct PriceHandler {
-    rpc_url: String,
+    chain: Arc<ChainContext>,
 }
 
 impl PriceHandler {
-    pub fn new(rpc_url: String) -> Self {
-        Self { rpc_url }
+    pub fn new(chain: Arc<ChainContext>) -> Self {
+        Self { chain }
     }
 
     pub async fn handle_get_token_price(
         &self,
-        Parameters(GetTokenPriceRequest { token, fee }): Parameters<GetTokenPriceRequest>,
+        Parameters(GetTokenPriceRequest {
+            token,
+            fee,
+            source,
+            quote_token,
+            max_age_secs,
+            twap_window_secs,
+            chain: _,
+        }): Parameters<GetTokenPriceRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let provider = ProviderBuilder::new()
-            .connect(&self.rpc_url)
-            .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to connect to RPC: {}", e), None)
-            })?;
-        let token_address = Address::from_str(token.as_str())
-            .map_err(|e| McpError::invalid_params(format!("Invalid token address: {}", e), None))?;
-        // let quote = IQuoterV2::new(QUOTER_ADDRESS, provider.clone());
-        let quoter = IQuoter::new(QUOTER_ADDRESS, provider.clone());
-        let token_contract = IERC20::new(token_address, provider.clone());
-        let decimals = token_contract.decimals().call().await.map_err(|e| {
-            McpError::internal_error(format!("Failed to get token decimals: {}", e), None)
-        })?;
-        let one_token = U256::from(10).pow(U256::from(decimals));
+        let provider = self.chain.provider.clone();
+        let chain = &self.chain.addresses;
+        let token_address = self.chain.resolve_token(&token)?;
+        let quote = self.parse_quote_token(quote_token.as_deref())?;
+
+        let response = match source.unwrap_or(PriceSource::Uniswap) {
+            PriceSource::Uniswap => {
+                uniswap_price(provider.clone(), chain, token_address, &quote, fee).await?
+            }
+            PriceSource::Chainlink => {
+                if !quote.is_usd {
+                    return Err(McpError::invalid_params(
+                        "Chainlink source only supports quote_token \"USD\"",
+                        None,
+                    ));
+                }
+                chainlink_price(provider.clone(), chain, token_address, max_age_secs).await?
+            }
+            // 优先使用 Chainlink，没有喂价或喂价校验失败时回退到 Uniswap 报价
+            PriceSource::Auto if quote.is_usd => {
+                match chainlink_price(provider.clone(), chain, token_address, max_age_secs).await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        uniswap_price(provider.clone(), chain, token_address, &quote, fee).await?
+                    }
+                }
+            }
+            PriceSource::Auto => {
+                uniswap_price(provider.clone(), chain, token_address, &quote, fee).await?
+            }
+            PriceSource::Spot => {
+                pool_state_price(provider.clone(), chain, token_address, &quote, fee, None).await?
+            }
+            PriceSource::Twap => {
+                let window = twap_window_secs.unwrap_or(DEFAULT_TWAP_WINDOW_SECS);
+                pool_state_price(
+                    provider.clone(),
+                    chain,
+                    token_address,
+                    &quote,
+                    fee,
+                    Some(window),
+                )
+                .await?
+            }
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+
+    pub async fn handle_get_price_consensus(
+        &self,
+        Parameters(GetPriceConsensusRequest {
+            token,
+            fee,
+            max_age_secs,
+            twap_window_secs,
+            max_deviation_bps,
+            chain: _,
+        }): Parameters<GetPriceConsensusRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let token_address = self.chain.resolve_token(&token)?;
 
-        let data = quoter
-            .quoteExactInputSingle(
+        let response = self
+            .price_consensus(
                 token_address,
-                USDT_ADDRESS,
-                U24::from(fee.unwrap_or(3000)),
-                one_token,
-                U160::ZERO,
+                fee,
+                max_age_secs,
+                twap_window_secs.unwrap_or(DEFAULT_TWAP_WINDOW_SECS),
+                max_deviation_bps.unwrap_or(DEFAULT_MAX_DEVIATION_BPS),
             )
-            .call()
-            .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to get token price quote: {}", e), None)
-            })?;
-        // let usd_out = quote.quoteExactInputSingle()
+            .await?;
 
         Ok(CallToolResult::success(vec![Content::text(
-            GetTokenPriceResponse {
-                price: MetaData {
-                    value: data,
-                    decimals: 6,
-                    symbol: "USDT".to_string(),
-                },
-            }
-            .to_string(),
+            response.to_string(),
         )]))
     }
+
+    /// 同时获取 Uniswap 报价、Chainlink 喂价和池子 TWAP 的 USD 价格，计算中位数和最大偏差
+    pub async fn price_consensus(
+        &self,
+        token_address: Address,
+        fee: Option<u32>,
+        max_age: Option<u64>,
+        window: u32,
+        threshold_bps: u32,
+    ) -> Result<GetPriceConsensusResponse, McpError> {
+        let provider = self.chain.provider.clone();
+        let chain = &self.chain.addresses;
+        let quote = self.parse_quote_token(None)?;
+
+        // 单个来源失败不影响其余来源，错误记录在响应中
+        let (uniswap, chainlink, twap) = tokio::join!(
+            uniswap_price(provider.clone(), chain, token_address, &quote, fee),
+            chainlink_price(provider.clone(), chain, token_address, max_age),
+            pool_state_price(
+                provider.clone(),
+                chain,
+                token_address,
+                &quote,
+                fee,
+                Some(window)
+            ),
+        );
+        let prices: Vec<SourcePrice> = [
+            (PriceSource::Uniswap, uniswap),
+            (PriceSource::Chainlink, chainlink),
+            (PriceSource::Twap, twap),
+        ]
+        .into_iter()
+        .map(|(source, result)| match result {
+            Ok(response) => SourcePrice {
+                source,
+                price: Some(response.price),
+                error: None,
+            },
+            Err(e) => SourcePrice {
+                source,
+                price: None,
+                error: Some(e.message.to_string()),
+            },
+        })
+        .collect();
+
+        let values: Vec<U256> = prices
+            .iter()
+            .filter_map(|source| source.price.as_ref())
+            .map(|price| consensus::normalize(price.value, price.decimals))
+            .collect();
+        let consensus = consensus::evaluate(&values, threshold_bps).ok_or_else(|| {
+            McpError::internal_error(
+                format!("No price source available for token {}", token_address),
+                None,
+            )
+        })?;
+
+        Ok(GetPriceConsensusResponse {
+            token: token_address.to_string(),
+            prices,
+            median: MetaData {
+                value: consensus.median,
+                decimals: CONSENSUS_DECIMALS,
+                symbol: USD_QUOTE.to_string(),
+            },
+            max_deviation_bps: consensus.max_deviation_bps,
+            threshold_bps,
+            flagged: consensus.flagged,
+        })
+    }
+
+    /// 代币的 USD 价格，与 source = "auto" 相同：优先 Chainlink，失败时回退到 Uniswap 报价
+    pub async fn usd_price(&self, token_address: Address) -> Result<MetaData, McpError> {
+        let provider = self.chain.provider.clone();
+        let chain = &self.chain.addresses;
+        match chainlink_price(provider.clone(), chain, token_address, None).await {
+            Ok(response) => Ok(response.price),
+            Err(_) => {
+                let quote = self.parse_quote_token(None)?;
+                let response = uniswap_price(provider, chain, token_address, &quote, None).await?;
+                Ok(response.price)
+            }
+        }
+    }
+
+    /// 解析计价代币："USD" 使用 USDC 报价，"ETH" 使用 WETH 报价，其余按符号或地址解析
+    fn parse_quote_token(&self, quote_token: Option<&str>) -> Result<QuoteToken, McpError> {
+        let chain = &self.chain.addresses;
+        let quote_token = quote_token.unwrap_or(USD_QUOTE);
+        if quote_token.eq_ignore_ascii_case(USD_QUOTE) {
+            return Ok(QuoteToken {
+                address: chain.usdc,
+                symbol: None,
+                is_usd: true,
+            });
+        }
+        if quote_token.eq_ignore_ascii_case(NATIVE_ETH) {
+            return Ok(QuoteToken {
+                address: chain.weth,
+                symbol: Some(NATIVE_ETH.to_string()),
+                is_usd: false,
+            });
+        }
+        let address = self.chain.resolve_token(quote_token).map_err(|e| {
+            McpError::invalid_params(format!("Invalid quote_token: {}", e.message), None)
+        })?;
+        Ok(QuoteToken {
+            address,
+            symbol: None,
+            is_usd: false,
+        })
+    }
+}
+
+/// 读取代币精度，返回 1 个完整代币对应的原始数量
+async fn one_token<P: Provider>(provider: P, token_address: Address) -> Result<U256, McpError> {
+    let token_contract = IERC20::new(token_address, provider);
+    let decimals = token_contract.decimals().call().await.map_err(|e| {
+        McpError::internal_error(format!("Failed to get token decimals: {}", e), None)
+    })?;
+    Ok(U256::from(10).pow(U256::from(decimals)))
+}
+
+/// 从链上读取计价代币的精度和符号
+async fn quote_metadata<P: Provider>(
+    provider: P,
+    quote: &QuoteToken,
+) -> Result<(u8, String), McpError> {
+    let quote_contract = IERC20::new(quote.address, provider);
+    let quote_decimals = quote_contract.decimals().call().await.map_err(|e| {
+        McpError::internal_error(format!("Failed to get quote token decimals: {}", e), None)
+    })?;
+    let quote_symbol = match &quote.symbol {
+        Some(symbol) => symbol.clone(),
+        None => quote_contract.symbol().call().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get quote token symbol: {}", e), None)
+        })?,
+    };
+    Ok((quote_decimals, quote_symbol))
+}
+
+/// 通过 Uniswap V3 报价 1 个代币可以换到的计价代币数量
+async fn uniswap_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_address: Address,
+    quote: &QuoteToken,
+    fee: Option<u32>,
+) -> Result<GetTokenPriceResponse, McpError> {
+    let one_token = one_token(provider.clone(), token_address).await?;
+    let (quote_decimals, quote_symbol) = quote_metadata(provider.clone(), quote).await?;
+
+    // 代币与计价代币相同时价格恒为 1
+    let route = if token_address == quote.address {
+        Route {
+            tokens: vec![],
+            fees: vec![],
+            amount_in: one_token,
+            amount_out: U256::from(10).pow(U256::from(quote_decimals)),
+            gas_estimate: U256::ZERO,
+        }
+    } else {
+        quote_direct_or_via_weth(
+            provider,
+            chain,
+            token_address,
+            quote.address,
+            one_token,
+            fee,
+        )
+        .await?
+    };
+
+    Ok(GetTokenPriceResponse {
+        price: MetaData {
+            value: route.amount_out,
+            decimals: quote_decimals,
+            symbol: quote_symbol,
+        },
+        source: PriceSource::Uniswap,
+        path: route.tokens.iter().map(|token| token.to_string()).collect(),
+        fees: route.fees,
+        updated_at: None,
+        round_id: None,
+        window_secs: None,
+    })
+}
+
+/// 直接从池子状态计算价格：window 为 None 时读取 slot0 即时价格，否则计算 TWAP
+async fn pool_state_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_address: Address,
+    quote: &QuoteToken,
+    fee: Option<u32>,
+    window: Option<u32>,
+) -> Result<GetTokenPriceResponse, McpError> {
+    if token_address == quote.address {
+        return Err(McpError::invalid_params(
+            "token and quote_token must be different for pool prices",
+            None,
+        ));
+    }
+    let one_token = one_token(provider.clone(), token_address).await?;
+    let (quote_decimals, quote_symbol) = quote_metadata(provider.clone(), quote).await?;
+
+    let (price, source) = match window {
+        None => (
+            pool_price::spot_price(
+                provider,
+                chain,
+                token_address,
+                quote.address,
+                one_token,
+                fee,
+            )
+            .await?,
+            PriceSource::Spot,
+        ),
+        Some(window) => (
+            pool_price::twap_price(
+                provider,
+                chain,
+                token_address,
+                quote.address,
+                one_token,
+                fee,
+                window,
+            )
+            .await?,
+            PriceSource::Twap,
+        ),
+    };
+
+    Ok(GetTokenPriceResponse {
+        price: MetaData {
+            value: price.amount_out,
+            decimals: quote_decimals,
+            symbol: quote_symbol,
+        },
+        source,
+        path: vec![token_address.to_string(), quote.address.to_string()],
+        fees: vec![price.fee],
+        updated_at: None,
+        round_id: None,
+        window_secs: window,
+    })
+}
+
+/// 优先使用直连池子报价，没有直连池子时经 WETH 两跳报价
+async fn quote_direct_or_via_weth<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    fee: Option<u32>,
+) -> Result<Route, McpError> {
+    // 未指定 fee 时探测所有费率档位，选择报价最优的池子
+    let direct =
+        fee_tier::quote_exact_input(provider.clone(), chain, token_in, token_out, amount_in, fee)
+            .await;
+    match direct {
+        Ok(quote) => Ok(Route {
+            tokens: vec![token_in, token_out],
+            fees: vec![quote.fee],
+            amount_in,
+            amount_out: quote.amount_out,
+            gas_estimate: quote.gas_estimate,
+        }),
+        Err(direct_err) if token_in != chain.weth && token_out != chain.weth => {
+            routing::quote_path(
+                provider,
+                chain,
+                vec![token_in, chain.weth, token_out],
+                amount_in,
+            )
+            .await
+            .map_err(|weth_err| {
+                McpError::invalid_params(
+                    format!(
+                        "No direct price for {} / {} ({}), and routing via WETH failed: {}",
+                        token_in, token_out, direct_err.message, weth_err.message
+                    ),
+                    None,
+                )
+            })
+        }
+        Err(direct_err) => Err(direct_err),
+    }
+}
+
+/// 读取 Chainlink USD 喂价
+async fn chainlink_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_address: Address,
+    max_age: Option<u64>,
+) -> Result<GetTokenPriceResponse, McpError> {
+    let price = chainlink::latest_usd_price(provider, chain, token_address, max_age).await?;
+
+    Ok(GetTokenPriceResponse {
+        price: MetaData {
+            value: price.value,
+            decimals: price.decimals,
+            symbol: "USD".to_string(),
+        },
+        source: PriceSource::Chainlink,
+        path: vec![],
+        fees: vec![],
+        updated_at: Some(price.updated_at),
+        round_id: Some(price.round_id),
+        window_secs: None,
+    })
 }
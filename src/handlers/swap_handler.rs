@@ -1,30 +1,113 @@
-use crate::models::{IQuoterV2, ISwapRouter, IERC20};
-use crate::models::{MetaData, SwapTokensRequest, SwapTokensResponse};
-use crate::models::{QUOTERV2_ADDRESS, ROUTER_ADDRESS};
+use crate::config::PolicyConfig;
+use crate::handlers::PriceHandler;
+use crate::models::{
+    GasReport, MetaData, SwapExecution, SwapProtocol, SwapSide, SwapTokensRequest,
+    SwapTokensResponse,
+};
+use crate::models::{
+    ISwapRouter, IUniswapV2Factory, IUniswapV2Pair, IUniswapV2Router02, IERC20, IWETH9,
+};
+use crate::services::approval;
+use crate::services::fee_tier;
+use crate::services::gas::{self, FeeEstimate};
+use crate::services::pool_price::DEFAULT_TWAP_WINDOW_SECS;
+use crate::services::routing::{self, Route};
+use crate::services::ChainContext;
+use alloy::network::TransactionBuilder;
 use alloy::primitives::aliases::U24;
 use alloy::primitives::utils::parse_units;
-use alloy::primitives::{Address, U160, U256};
-use alloy::providers::ProviderBuilder;
+use alloy::primitives::{Address, Bytes, U160, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::state::StateOverride;
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
 use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::{SolCall, SolEvent};
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::{model::*, ErrorData as McpError};
-use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// exact_in 时 slippage_pct 的上限（基点），100% 表示不限制最小到手数量
+const MAX_EXACT_IN_SLIPPAGE_BPS: u128 = 10_000;
+
+/// exact_out 时 slippage_pct 的上限（基点），最大花费不超过报价的 1.5 倍
+const MAX_EXACT_OUT_SLIPPAGE_BPS: u128 = 5_000;
+
+/// 近似 gas 中路由合约本身的开销：转账、deadline 检查和 multicall 解包
+const ROUTER_GAS_OVERHEAD: u64 = 60_000;
+
+/// 近似 gas 中 V2 每一跳交换的开销，V2 报价没有 gas 估算
+const V2_POOL_SWAP_GAS: u64 = 90_000;
+
+/// 单个交易场所的报价结果
+#[derive(Debug, Clone)]
+struct SwapQuote {
+    protocol: SwapProtocol, // 只会是 V2 或 V3
+    route: Route,           // V2 的费率固定为 0.3%
+}
+
+/// 编码后的路由合约调用
+#[derive(Debug, Clone)]
+struct SwapCall {
+    to: Address,
+    input: Bytes,
+    value: U256,
+    multicall: bool, // 返回值需要先按 multicall 的 bytes[] 解码
+}
+
+/// WETH 的 Deposit/Withdrawal 事件中的数量字段
+trait WethAmount {
+    fn wad(&self) -> U256;
+}
+
+impl WethAmount for IWETH9::Deposit {
+    fn wad(&self) -> U256 {
+        self.wad
+    }
+}
+
+impl WethAmount for IWETH9::Withdrawal {
+    fn wad(&self) -> U256 {
+        self.wad
+    }
+}
+
+/// 构造交换交易所需的参数
+#[derive(Debug, Clone)]
+struct SwapPlan {
+    side: SwapSide,
+    from_token: Address,
+    to_token: Address,
+    recipient: Address,
+    router: Address,  // 所选交易场所的路由合约，同时也是授权对象
+    native_in: bool,  // 以原生代币支付，随交易附带 msg.value
+    native_out: bool, // 收到包装代币后由路由合约解包为原生代币
+    amount_in: U256,  // exact_in 时为精确输入，exact_out 时为 amountInMaximum
+    amount_out: U256, // exact_in 时为 amountOutMinimum，exact_out 时为精确输出
+    deadline: U256,
+}
+
 #[derive(Clone)]
 pub struct SwapHandler {
     private_key: String,
-    rpc_url: String,
+    chain: Arc<ChainContext>,
+    policy: PolicyConfig,
 }
 
 impl SwapHandler {
-    pub fn new(private_key: String, rpc_url: String) -> Self {
+    pub fn new(private_key: String, chain: Arc<ChainContext>) -> Self {
         Self {
             private_key,
-            rpc_url,
+            chain,
+            policy: PolicyConfig::default(),
         }
     }
 
+    pub fn with_policy(mut self, policy: PolicyConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
     pub async fn handle_swap_tokens(
         &self,
         Parameters(SwapTokensRequest {
@@ -33,78 +116,228 @@ impl SwapHandler {
             amount,
             slippage_pct,
             fee,
+            dry_run,
+            protocol,
+            side,
+            max_price_deviation_bps,
+            fee_speed,
+            chain: _,
         }): Parameters<SwapTokensRequest>,
     ) -> Result<CallToolResult, McpError> {
+        // 滑点超过上限时 amountOutMinimum/amountInMaximum 会下溢或失去保护作用
+        let side = side.unwrap_or(SwapSide::ExactIn);
+        let max_slippage = match side {
+            SwapSide::ExactIn => MAX_EXACT_IN_SLIPPAGE_BPS,
+            SwapSide::ExactOut => MAX_EXACT_OUT_SLIPPAGE_BPS,
+        };
+        if slippage_pct > max_slippage {
+            return Err(McpError::invalid_params(
+                format!(
+                    "slippage_pct {} exceeds the maximum of {} bps for {}",
+                    slippage_pct, max_slippage, side
+                ),
+                None,
+            ));
+        }
+
+        // 按配置的交易策略检查请求
+        let dry_run = dry_run.unwrap_or(false);
+        if !self.policy.allow_broadcast && !dry_run {
+            return Err(McpError::invalid_params(
+                "Broadcasting swaps is disabled by policy, set dry_run to true",
+                None,
+            ));
+        }
+        if let Some(max_slippage_bps) = self.policy.max_slippage_bps {
+            if slippage_pct > u128::from(max_slippage_bps) {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "slippage_pct {} exceeds the policy limit of {} bps",
+                        slippage_pct, max_slippage_bps
+                    ),
+                    None,
+                ));
+            }
+        }
+        let max_price_deviation_bps =
+            max_price_deviation_bps.or(self.policy.max_price_deviation_bps);
+
         // 验证私钥格式
         let signer: PrivateKeySigner = self
             .private_key
             .parse()
             .map_err(|e| McpError::invalid_params(format!("Invalid private key: {}", e), None))?;
 
-        // 连接到提供者
+        // 在共享 provider 上叠加签名钱包和 nonce/gas 填充，不会建立新连接
         let provider = ProviderBuilder::new()
-            .connect(self.rpc_url.as_str())
-            .await
-            .map_err(|e| {
-                McpError::internal_error(format!("Failed to connect to RPC: {}", e), None)
-            })?;
+            .wallet(signer.clone())
+            .connect_provider(self.chain.provider.clone());
 
-        // 验证代币地址格式
-        let from_token_address = Address::from_str(from_token.as_str()).map_err(|e| {
-            McpError::invalid_params(
-                format!("Invalid from_token address '{}': {}", from_token, e),
-                None,
-            )
-        })?;
-        let to_token_address = Address::from_str(to_token.as_str()).map_err(|e| {
-            McpError::invalid_params(
-                format!("Invalid to_token address '{}': {}", to_token, e),
-                None,
-            )
-        })?;
+        // 验证代币地址格式，原生代币在链上按包装代币路由
+        let (from_token_address, native_in) = self.parse_token(&from_token, "from_token")?;
+        let (to_token_address, native_out) = self.parse_token(&to_token, "to_token")?;
+        let native_symbol = self.chain.addresses.native_symbol;
 
         // 检查是否为相同代币
         if from_token_address == to_token_address {
+            if native_in != native_out {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "{} <-> W{} is a wrap/unwrap, not a swap",
+                        native_symbol, native_symbol
+                    ),
+                    None,
+                ));
+            }
             return Err(McpError::invalid_params("Cannot swap the same token", None));
         }
 
         // 获取代币信息
         let from_token_contract = IERC20::new(from_token_address, provider.clone());
-        let from_token_decimals = from_token_contract.decimals().call().await.map_err(|e| {
-            McpError::internal_error(format!("Failed to get from_token decimals: {}", e), None)
-        })?;
+        let (from_token_decimals, from_token_symbol) = if native_in {
+            (18, native_symbol.to_string())
+        } else {
+            let decimals = from_token_contract.decimals().call().await.map_err(|e| {
+                McpError::internal_error(format!("Failed to get from_token decimals: {}", e), None)
+            })?;
+            let symbol = from_token_contract.symbol().call().await.map_err(|e| {
+                McpError::internal_error(format!("Failed to get from_token symbol: {}", e), None)
+            })?;
+            (decimals, symbol)
+        };
+        let (decimals, symbol) = if native_out {
+            (18, native_symbol.to_string())
+        } else {
+            let to_token_contract = IERC20::new(to_token_address, provider.clone());
+            let decimals = to_token_contract.decimals().call().await.map_err(|e| {
+                McpError::internal_error(format!("Failed to get to_token decimals: {}", e), None)
+            })?;
+            let symbol = to_token_contract.symbol().call().await.map_err(|e| {
+                McpError::internal_error(format!("Failed to get to_token symbol: {}", e), None)
+            })?;
+            (decimals, symbol)
+        };
 
-        // 解析金额
-        let amount_in: U256 = parse_units(amount.to_string().as_str(), from_token_decimals)
+        // 解析金额：exact_in 时为 from_token 数量，exact_out 时为 to_token 数量
+        let amount_decimals = match side {
+            SwapSide::ExactIn => from_token_decimals,
+            SwapSide::ExactOut => decimals,
+        };
+        let amount_raw: U256 = parse_units(amount.to_string().as_str(), amount_decimals)
             .map_err(|e| {
                 McpError::invalid_params(format!("Invalid amount '{}': {}", amount, e), None)
             })?
             .into();
 
         // 验证金额不为零
-        if amount_in == U256::ZERO {
+        if amount_raw == U256::ZERO {
             return Err(McpError::invalid_params("Amount cannot be zero", None));
         }
 
-        // 获取报价
-        let quoter_v2 = IQuoterV2::new(QUOTERV2_ADDRESS, provider.clone());
-        let params = IQuoterV2::QuoteExactInputSingleParams {
-            tokenIn: from_token_address,
-            tokenOut: to_token_address,
-            amountIn: amount_in.into(),
-            fee: U24::from(fee.unwrap_or(3000)),
-            sqrtPriceLimitX96: U160::ZERO,
+        // 获取报价，auto 模式下选择到手数量更多的交易场所；未指定 fee 时搜索 V3 最优路径和费率档位
+        let quote = match (side, protocol.unwrap_or(SwapProtocol::V3)) {
+            (SwapSide::ExactOut, SwapProtocol::V2) => {
+                return Err(McpError::invalid_params(
+                    "exact_out swaps are only supported on Uniswap V3",
+                    None,
+                ))
+            }
+            (SwapSide::ExactOut, _) => {
+                Self::quote_v3_exact_out(
+                    provider.clone(),
+                    &self.chain,
+                    from_token_address,
+                    to_token_address,
+                    amount_raw,
+                    fee,
+                )
+                .await?
+            }
+            (SwapSide::ExactIn, SwapProtocol::V2) => {
+                Self::quote_v2(
+                    provider.clone(),
+                    &self.chain,
+                    from_token_address,
+                    to_token_address,
+                    amount_raw,
+                )
+                .await?
+            }
+            (SwapSide::ExactIn, SwapProtocol::V3) => {
+                Self::quote_v3(
+                    provider.clone(),
+                    &self.chain,
+                    from_token_address,
+                    to_token_address,
+                    amount_raw,
+                    fee,
+                )
+                .await?
+            }
+            (SwapSide::ExactIn, SwapProtocol::Auto) => {
+                let (v2, v3) = tokio::join!(
+                    Self::quote_v2(
+                        provider.clone(),
+                        &self.chain,
+                        from_token_address,
+                        to_token_address,
+                        amount_raw
+                    ),
+                    Self::quote_v3(
+                        provider.clone(),
+                        &self.chain,
+                        from_token_address,
+                        to_token_address,
+                        amount_raw,
+                        fee,
+                    ),
+                );
+                match (v2, v3) {
+                    (Ok(v2), Ok(v3)) => {
+                        if v2.route.amount_out > v3.route.amount_out {
+                            v2
+                        } else {
+                            v3
+                        }
+                    }
+                    (Ok(v2), Err(_)) => v2,
+                    (Err(_), Ok(v3)) => v3,
+                    (Err(v2_err), Err(v3_err)) => {
+                        return Err(McpError::internal_error(
+                            format!(
+                                "Failed to get quote from any venue: v2: {}; v3: {}",
+                                v2_err.message, v3_err.message
+                            ),
+                            None,
+                        ))
+                    }
+                }
+            }
         };
 
-        let res = quoter_v2
-            .quoteExactInputSingle(params)
-            .call()
-            .await
-            .map_err(|e| McpError::internal_error(format!("Failed to get quote: {}", e), None))?;
-
-        // 根据滑点计算 amountOutMinimum
-        let slippage_multiplier = 10000 - slippage_pct;
-        let amount_out_min = res.amountOut * U256::from(slippage_multiplier) / U256::from(10000);
+        // 根据滑点计算 amountOutMinimum（exact_in）或 amountInMaximum（exact_out），滑点已在入口校验过上限
+        let invalid_slippage =
+            || McpError::invalid_params(format!("Invalid slippage_pct {}", slippage_pct), None);
+        let (amount_in, amount_out) = match side {
+            SwapSide::ExactIn => {
+                let factor = 10000u128
+                    .checked_sub(slippage_pct)
+                    .ok_or_else(invalid_slippage)?;
+                (
+                    quote.route.amount_in,
+                    quote.route.amount_out * U256::from(factor) / U256::from(10000),
+                )
+            }
+            SwapSide::ExactOut => {
+                let factor = 10000u128
+                    .checked_add(slippage_pct)
+                    .ok_or_else(invalid_slippage)?;
+                (
+                    quote.route.amount_in * U256::from(factor) / U256::from(10000),
+                    quote.route.amount_out,
+                )
+            }
+        };
 
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -114,65 +347,772 @@ impl SwapHandler {
             .as_secs();
         let deadline = now + 20 * 60; // 20 分钟有效期
 
-        let swap_params = ISwapRouter::ExactInputSingleParams {
-            tokenIn: from_token_address,
-            tokenOut: to_token_address,
-            fee: U24::from(3000),
+        // 该链未部署所选交易场所的路由合约时无法执行
+        let router = match quote.protocol {
+            SwapProtocol::V2 => self.chain.v2_router()?,
+            _ => self.chain.swap_router()?,
+        };
+
+        let plan = SwapPlan {
+            side,
+            from_token: from_token_address,
+            to_token: to_token_address,
             recipient: signer.address(),
-            amountIn: amount_in.into(),
+            router,
+            native_in,
+            native_out,
+            amount_in,
+            amount_out,
             deadline: U256::from(deadline),
-            amountOutMinimum: amount_out_min,
-            sqrtPriceLimitX96: U160::ZERO,
         };
 
-        // 获取目标代币信息
-        let to_token_contract = IERC20::new(to_token_address, provider.clone());
-        let decimals = to_token_contract.decimals().call().await.map_err(|e| {
-            McpError::internal_error(format!("Failed to get to_token decimals: {}", e), None)
-        })?;
-        let symbol = to_token_contract.symbol().call().await.map_err(|e| {
-            McpError::internal_error(format!("Failed to get to_token symbol: {}", e), None)
+        // exact_out 时同时报告预计花费和最大花费
+        let (expected_in, max_in) = match side {
+            SwapSide::ExactIn => (None, None),
+            SwapSide::ExactOut => (
+                Some(MetaData {
+                    value: quote.route.amount_in,
+                    decimals: from_token_decimals,
+                    symbol: from_token_symbol.clone(),
+                }),
+                Some(MetaData {
+                    value: plan.amount_in,
+                    decimals: from_token_decimals,
+                    symbol: from_token_symbol.clone(),
+                }),
+            ),
+        };
+
+        // 以原生代币支付时无需授权
+        let allowance = if native_in {
+            U256::MAX
+        } else {
+            from_token_contract
+                .allowance(signer.address(), plan.router)
+                .call()
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to get token allowance: {}", e), None)
+                })?
+        };
+
+        // 手续费按 eth_feeHistory 的百分位估算，原生代币价格仅用于报告 USD 成本
+        let speed = fee_speed.unwrap_or_default();
+        let fees = gas::estimate_fees(&provider, speed).await?;
+        let native_usd = PriceHandler::new(self.chain.clone())
+            .usd_price(self.chain.addresses.wrapped_native)
+            .await
+            .ok();
+
+        let gas_report = |estimate: u64| {
+            gas::gas_report(
+                speed,
+                estimate,
+                gas::gas_limit_with_buffer(estimate),
+                &fees,
+                native_symbol,
+                native_usd.as_ref(),
+            )
+        };
+
+        // 授权不足时需要先发送 approve，单独估算 approve 的 gas
+        let needs_approval = allowance < plan.amount_in;
+        let approval_gas = if needs_approval {
+            let estimate = from_token_contract
+                .approve(plan.router, plan.amount_in)
+                .from(signer.address())
+                .estimate_gas()
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(
+                        format!("Failed to estimate approval gas: {}", e),
+                        None,
+                    )
+                })?;
+            Some(gas_report(estimate))
+        } else {
+            None
+        };
+
+        // 交换交易的 gas：授权足够时直接估算；需要授权时 dry_run 尝试用 state override 覆盖授权额度后估算，
+        // 广播时在 approve 确认后重新估算。无法精确估算时按报价估算近似值
+        let (swap_estimate, overrides) = if !needs_approval {
+            let estimate = Self::estimate_swap_gas(provider.clone(), &quote, &plan, None).await?;
+            (Some(estimate), None)
+        } else if dry_run {
+            Self::estimate_swap_gas_before_approval(provider.clone(), &quote, &plan).await?
+        } else {
+            (None, None)
+        };
+        let mut swap_gas =
+            gas_report(swap_estimate.unwrap_or_else(|| Self::approximate_swap_gas(&quote)));
+        swap_gas.approximate = swap_estimate.is_none();
+        let total = |swap_gas: &GasReport| match &approval_gas {
+            Some(approval_gas) => gas::total_cost(&[swap_gas, approval_gas]),
+            None => gas::total_cost(&[swap_gas]),
+        };
+        let (mut total_cost, mut total_cost_usd) = total(&swap_gas);
+
+        // 原生代币余额需覆盖交换附带的 value 以及 approve 和交换交易的最大 gas 费用
+        let max_gas_cost = fees.max_cost(swap_gas.gas_limit)
+            + approval_gas
+                .as_ref()
+                .map(|approval_gas| fees.max_cost(approval_gas.gas_limit))
+                .unwrap_or(U256::ZERO);
+        gas::check_native_balance(
+            &provider,
+            signer.address(),
+            Self::swap_call(&quote, &plan).value,
+            max_gas_cost,
+            native_symbol,
+        )
+        .await?;
+
+        if dry_run {
+            // 需要授权但无法覆盖授权额度时模拟会因授权不足而 revert，此时按报价给出预计数量
+            let value = if needs_approval && overrides.is_none() {
+                match side {
+                    SwapSide::ExactIn => quote.route.amount_out,
+                    SwapSide::ExactOut => quote.route.amount_in,
+                }
+            } else {
+                Self::simulate_swap(provider.clone(), &quote, &plan, overrides).await?
+            };
+            // exact_out 时路由合约返回的是实际花费，到手数量固定为目标数量
+            let (value, expected_in) = match side {
+                SwapSide::ExactIn => (value, expected_in),
+                SwapSide::ExactOut => (
+                    quote.route.amount_out,
+                    Some(MetaData {
+                        value,
+                        decimals: from_token_decimals,
+                        symbol: from_token_symbol.clone(),
+                    }),
+                ),
+            };
+
+            return Ok(CallToolResult::success(vec![Content::text(
+                SwapTokensResponse {
+                    protocol: quote.protocol,
+                    side,
+                    path: quote.route.tokens.iter().map(|t| t.to_string()).collect(),
+                    fees: quote.route.fees.clone(),
+                    estimated_out: MetaData {
+                        value,
+                        decimals,
+                        symbol,
+                    },
+                    expected_in,
+                    max_in,
+                    gas: swap_gas,
+                    approval_gas,
+                    total_cost,
+                    total_cost_usd,
+                    execution: None,
+                }
+                .to_string(),
+            )]));
+        }
+
+        // 广播前检查两侧代币的价格共识，偏差超限时拒绝执行
+        if let Some(threshold_bps) = max_price_deviation_bps {
+            self.check_price_consensus(&[from_token_address, to_token_address], threshold_bps)
+                .await?;
+        }
+
+        // 授权不足时先发送 approve 交易
+        let approval_receipt = match &approval_gas {
+            Some(approval_gas) => {
+                let receipt = from_token_contract
+                    .approve(plan.router, plan.amount_in)
+                    .gas(approval_gas.gas_limit)
+                    .max_fee_per_gas(fees.max_fee_per_gas)
+                    .max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(format!("Failed to send approval: {}", e), None)
+                    })?
+                    .get_receipt()
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(
+                            format!("Failed to get approval receipt: {}", e),
+                            None,
+                        )
+                    })?;
+                if !receipt.status() {
+                    return Err(McpError::internal_error(
+                        format!("Approval transaction {} reverted", receipt.transaction_hash),
+                        None,
+                    ));
+                }
+                Some(receipt)
+            }
+            None => None,
+        };
+
+        // approve 确认后授权已生效，按实际状态估算交换交易的 gas
+        if swap_gas.approximate {
+            let estimate = Self::estimate_swap_gas(provider.clone(), &quote, &plan, None).await?;
+            swap_gas = gas_report(estimate);
+            (total_cost, total_cost_usd) = total(&swap_gas);
+        }
+
+        // 广播交换交易并等待回执
+        let receipt =
+            Self::send_swap(provider.clone(), &quote, &plan, swap_gas.gas_limit, &fees).await?;
+        if !receipt.status() {
+            return Err(McpError::internal_error(
+                format!("Swap transaction {} reverted", receipt.transaction_hash),
+                None,
+            ));
+        }
+
+        // 从 Transfer 日志中解析实际花费和实际到手数量，原生代币部分以包装代币的 Deposit/Withdrawal 为准
+        let wrapped_native = self.chain.addresses.wrapped_native;
+        let amount_in = if native_in {
+            Self::weth_flow::<IWETH9::Deposit>(&receipt, wrapped_native)
+        } else {
+            Self::transferred(&receipt, from_token_address, |from, _| {
+                from == signer.address()
+            })
+        };
+        let amount_out = if native_out {
+            Self::weth_flow::<IWETH9::Withdrawal>(&receipt, wrapped_native)
+        } else {
+            Self::transferred(&receipt, to_token_address, |_, to| to == signer.address())
+        };
+        let gas_price = gas::gwei(receipt.effective_gas_price);
+        let fee = [Some(&receipt), approval_receipt.as_ref()]
+            .into_iter()
+            .flatten()
+            .fold(U256::ZERO, |acc, receipt| {
+                acc + U256::from(receipt.gas_used) * U256::from(receipt.effective_gas_price)
+            });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            SwapTokensResponse {
+                protocol: quote.protocol,
+                side,
+                path: quote.route.tokens.iter().map(|t| t.to_string()).collect(),
+                fees: quote.route.fees.clone(),
+                estimated_out: MetaData {
+                    value: quote.route.amount_out,
+                    decimals,
+                    symbol: symbol.clone(),
+                },
+                expected_in,
+                max_in,
+                gas: swap_gas,
+                approval_gas,
+                total_cost,
+                total_cost_usd,
+                execution: Some(SwapExecution {
+                    tx_hash: receipt.transaction_hash.to_string(),
+                    block_number: receipt.block_number,
+                    amount_in: MetaData {
+                        value: amount_in,
+                        decimals: from_token_decimals,
+                        symbol: from_token_symbol,
+                    },
+                    amount_out: MetaData {
+                        value: amount_out,
+                        decimals,
+                        symbol,
+                    },
+                    gas_used: receipt.gas_used,
+                    gas_price,
+                    approval_tx_hash: approval_receipt
+                        .map(|receipt| receipt.transaction_hash.to_string()),
+                    fee: MetaData {
+                        value: fee,
+                        decimals: 18,
+                        symbol: native_symbol.to_string(),
+                    },
+                }),
+            }
+            .to_string(),
+        )]))
+    }
+
+    /// 依次检查各代币的 Uniswap / Chainlink / TWAP 价格是否一致，任一代币被标记即返回错误
+    async fn check_price_consensus(
+        &self,
+        tokens: &[Address],
+        threshold_bps: u32,
+    ) -> Result<(), McpError> {
+        let price_handler = PriceHandler::new(self.chain.clone());
+        for &token in tokens {
+            let consensus = price_handler
+                .price_consensus(token, None, None, DEFAULT_TWAP_WINDOW_SECS, threshold_bps)
+                .await?;
+            if consensus.flagged {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "Refusing to swap, price consensus check failed: {}",
+                        consensus
+                    ),
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 解析代币参数，返回链上地址以及是否为原生代币
+    fn parse_token(&self, token: &str, field: &str) -> Result<(Address, bool), McpError> {
+        let chain = &self.chain.addresses;
+        if token.eq_ignore_ascii_case(chain.native_symbol) {
+            return Ok((chain.wrapped_native, true));
+        }
+        let address = self.chain.resolve_token(token).map_err(|e| {
+            McpError::invalid_params(format!("Invalid {}: {}", field, e.message), None)
         })?;
+        Ok((address, false))
+    }
+
+    /// 汇总回执中包装代币的 Deposit 或 Withdrawal 数量
+    fn weth_flow<E: SolEvent + WethAmount>(
+        receipt: &TransactionReceipt,
+        wrapped_native: Address,
+    ) -> U256 {
+        receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| log.address() == wrapped_native)
+            .filter_map(|log| log.log_decode::<E>().ok())
+            .fold(U256::ZERO, |acc, event| acc + event.inner.data.wad())
+    }
+
+    /// 汇总回执中指定代币满足条件的 Transfer 数量
+    fn transferred(
+        receipt: &TransactionReceipt,
+        token: Address,
+        matches: impl Fn(Address, Address) -> bool,
+    ) -> U256 {
+        receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| log.address() == token)
+            .filter_map(|log| log.log_decode::<IERC20::Transfer>().ok())
+            .filter(|transfer| matches(transfer.inner.data.from, transfer.inner.data.to))
+            .fold(U256::ZERO, |acc, transfer| acc + transfer.inner.data.value)
+    }
+
+    /// 通过 V3 QuoterV2 获取报价，未指定费率时搜索经过中间代币的最优路径
+    async fn quote_v3<P: Provider + Clone>(
+        provider: P,
+        chain: &ChainContext,
+        from_token: Address,
+        to_token: Address,
+        amount_in: U256,
+        fee: Option<u32>,
+    ) -> Result<SwapQuote, McpError> {
+        let route = routing::quote_route(
+            provider,
+            &chain.addresses,
+            from_token,
+            to_token,
+            amount_in,
+            fee,
+        )
+        .await?;
+
+        Ok(SwapQuote {
+            protocol: SwapProtocol::V3,
+            route,
+        })
+    }
+
+    /// 通过 V3 QuoterV2 获取精确输出报价，未指定费率时选择花费最少的档位
+    async fn quote_v3_exact_out<P: Provider + Clone>(
+        provider: P,
+        chain: &ChainContext,
+        from_token: Address,
+        to_token: Address,
+        amount_out: U256,
+        fee: Option<u32>,
+    ) -> Result<SwapQuote, McpError> {
+        let quote = fee_tier::quote_exact_output(
+            provider,
+            &chain.addresses,
+            from_token,
+            to_token,
+            amount_out,
+            fee,
+        )
+        .await?;
+
+        Ok(SwapQuote {
+            protocol: SwapProtocol::V3,
+            route: Route {
+                tokens: vec![from_token, to_token],
+                fees: vec![quote.fee],
+                amount_in: quote.amount_in,
+                amount_out: quote.amount_out,
+                gas_estimate: quote.gas_estimate,
+            },
+        })
+    }
+
+    /// 通过 V2 Router 的 getAmountsOut 获取报价
+    async fn quote_v2<P: Provider + Clone>(
+        provider: P,
+        chain: &ChainContext,
+        from_token: Address,
+        to_token: Address,
+        amount_in: U256,
+    ) -> Result<SwapQuote, McpError> {
+        let router = IUniswapV2Router02::new(chain.v2_router()?, provider.clone());
 
-        // 模拟授权代币（不实际发送交易）
-        // let approve_call = from_token_contract
-        //     .approve(ROUTER_ADDRESS, amount_in.into())
-        //     .from(signer.address());
-
-        // // 只进行模拟调用，不发送实际交易
-        // let _ = approve_call.call().await.map_err(|e| {
-        //     McpError::internal_error(format!("Failed to simulate token approval: {}", e), None)
-        // })?;
-
-        // 执行交换模拟
-        let router = ISwapRouter::new(ROUTER_ADDRESS, provider.clone());
-        let value = router
-            .exactInputSingle(swap_params.clone())
-            .from(signer.address())
+        // 确认交易对存在且有流动性，避免 getAmountsOut 返回难以理解的错误
+        let factory_address = router.factory().call().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get V2 factory: {}", e), None)
+        })?;
+        let factory = IUniswapV2Factory::new(factory_address, provider.clone());
+        let pair_address = factory
+            .getPair(from_token, to_token)
+            .call()
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to get V2 pair: {}", e), None))?;
+        if pair_address == Address::ZERO {
+            return Err(McpError::invalid_params(
+                format!("No Uniswap V2 pair for {} / {}", from_token, to_token),
+                None,
+            ));
+        }
+        let reserves = IUniswapV2Pair::new(pair_address, provider)
+            .getReserves()
             .call()
             .await
             .map_err(|e| {
-                McpError::internal_error(format!("Failed to simulate swap: {}", e), None)
+                McpError::internal_error(format!("Failed to get V2 reserves: {}", e), None)
             })?;
-        let gas_estimate = router
-            .exactInputSingle(swap_params.clone())
-            .from(signer.address())
+        if reserves.reserve0.is_zero() || reserves.reserve1.is_zero() {
+            return Err(McpError::invalid_params(
+                format!("Uniswap V2 pair {} has no liquidity", pair_address),
+                None,
+            ));
+        }
+
+        let amounts = router
+            .getAmountsOut(amount_in, vec![from_token, to_token])
             .call()
             .await
             .map_err(|e| {
-                McpError::internal_error(format!("Failed to estimate gas: {}", e), None)
+                McpError::internal_error(format!("Failed to get V2 quote: {}", e), None)
             })?;
+        let amount_out = amounts.last().copied().unwrap_or(U256::ZERO);
 
-        Ok(CallToolResult::success(vec![Content::text(
-            SwapTokensResponse {
-                estimated_out: MetaData {
-                    value,
-                    decimals,
-                    symbol,
-                },
-                gas_price: gas_estimate.to_string(),
+        Ok(SwapQuote {
+            protocol: SwapProtocol::V2,
+            route: Route {
+                tokens: vec![from_token, to_token],
+                fees: vec![3000],
+                amount_in,
+                amount_out,
+                gas_estimate: U256::ZERO,
+            },
+        })
+    }
+
+    /// 编码路由合约调用：原生代币输入时附带 msg.value，需要解包或退回原生代币时通过 multicall 组合调用
+    fn swap_call(quote: &SwapQuote, plan: &SwapPlan) -> SwapCall {
+        let value = if plan.native_in {
+            plan.amount_in
+        } else {
+            U256::ZERO
+        };
+
+        if quote.protocol == SwapProtocol::V2 {
+            let path = quote.route.tokens.clone();
+            let input = if plan.native_in {
+                IUniswapV2Router02::swapExactETHForTokensCall {
+                    amountOutMin: plan.amount_out,
+                    path,
+                    to: plan.recipient,
+                    deadline: plan.deadline,
+                }
+                .abi_encode()
+            } else if plan.native_out {
+                IUniswapV2Router02::swapExactTokensForETHCall {
+                    amountIn: plan.amount_in,
+                    amountOutMin: plan.amount_out,
+                    path,
+                    to: plan.recipient,
+                    deadline: plan.deadline,
+                }
+                .abi_encode()
+            } else {
+                IUniswapV2Router02::swapExactTokensForTokensCall {
+                    amountIn: plan.amount_in,
+                    amountOutMin: plan.amount_out,
+                    path,
+                    to: plan.recipient,
+                    deadline: plan.deadline,
+                }
+                .abi_encode()
+            };
+            return SwapCall {
+                to: plan.router,
+                input: input.into(),
+                value,
+                multicall: false,
+            };
+        }
+
+        // 输出原生代币时先把包装代币留在路由合约，再由 unwrapWETH9 解包转给钱包
+        let recipient = if plan.native_out {
+            plan.router
+        } else {
+            plan.recipient
+        };
+        let swap = match plan.side {
+            SwapSide::ExactOut => ISwapRouter::exactOutputSingleCall {
+                params: Self::v3_exact_output_params(quote, plan, recipient),
             }
-            .to_string(),
-        )]))
+            .abi_encode(),
+            SwapSide::ExactIn if quote.route.is_single_hop() => ISwapRouter::exactInputSingleCall {
+                params: Self::v3_single_params(quote, plan, recipient),
+            }
+            .abi_encode(),
+            SwapSide::ExactIn => ISwapRouter::exactInputCall {
+                params: Self::v3_multi_hop_params(quote, plan, recipient),
+            }
+            .abi_encode(),
+        };
+
+        let mut calls = vec![Bytes::from(swap)];
+        if plan.native_out {
+            calls.push(
+                ISwapRouter::unwrapWETH9Call {
+                    amountMinimum: plan.amount_out,
+                    recipient: plan.recipient,
+                }
+                .abi_encode()
+                .into(),
+            );
+        }
+        // exact_out 以原生代币支付时，多付的部分需要退回
+        if plan.native_in && plan.side == SwapSide::ExactOut {
+            calls.push(ISwapRouter::refundETHCall {}.abi_encode().into());
+        }
+
+        if calls.len() == 1 {
+            SwapCall {
+                to: plan.router,
+                input: calls.remove(0),
+                value,
+                multicall: false,
+            }
+        } else {
+            SwapCall {
+                to: plan.router,
+                input: ISwapRouter::multicallCall { data: calls }
+                    .abi_encode()
+                    .into(),
+                value,
+                multicall: true,
+            }
+        }
+    }
+
+    /// 解析路由合约的返回值，exact_in 时为到手数量，exact_out 时为实际花费
+    fn decode_swap_output(
+        quote: &SwapQuote,
+        call: &SwapCall,
+        output: &[u8],
+    ) -> Result<U256, McpError> {
+        let decode_error = |e: alloy::sol_types::Error| {
+            McpError::internal_error(format!("Failed to decode swap result: {}", e), None)
+        };
+
+        // V2 的三种 swapExact* 都返回 uint256[] amounts
+        if quote.protocol == SwapProtocol::V2 {
+            let amounts =
+                IUniswapV2Router02::swapExactTokensForTokensCall::abi_decode_returns(output)
+                    .map_err(decode_error)?;
+            return Ok(amounts.last().copied().unwrap_or(U256::ZERO));
+        }
+
+        // V3 的 exactInput/exactInputSingle/exactOutputSingle 都只返回一个 uint256
+        let output = if call.multicall {
+            ISwapRouter::multicallCall::abi_decode_returns(output)
+                .map_err(decode_error)?
+                .into_iter()
+                .next()
+                .unwrap_or_default()
+        } else {
+            Bytes::copy_from_slice(output)
+        };
+        ISwapRouter::exactInputSingleCall::abi_decode_returns(&output).map_err(decode_error)
+    }
+
+    /// 通过 eth_call 模拟交换，exact_in 时返回预计到手数量，exact_out 时返回预计花费
+    ///
+    /// 授权不足时由 overrides 覆盖授权额度
+    async fn simulate_swap<P: Provider>(
+        provider: P,
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        overrides: Option<StateOverride>,
+    ) -> Result<U256, McpError> {
+        let call = Self::swap_call(quote, plan);
+        let tx = TransactionRequest::default()
+            .with_from(plan.recipient)
+            .with_to(call.to)
+            .with_input(call.input.clone())
+            .with_value(call.value);
+        let output = provider
+            .call(tx)
+            .overrides_opt(overrides)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to simulate swap: {}", e), None)
+            })?;
+        Self::decode_swap_output(quote, &call, &output)
+    }
+
+    /// dry_run 时在授权前估算交换交易的 gas，返回估算值和用于模拟的 state override
+    ///
+    /// 找不到授权额度的存储槽位或节点不支持 state override 时返回 (None, None)，由调用方使用近似值；
+    /// 交换本身 revert（例如滑点不足）时仍然报错
+    async fn estimate_swap_gas_before_approval<P: Provider + Clone>(
+        provider: P,
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+    ) -> Result<(Option<u64>, Option<StateOverride>), McpError> {
+        let Some(overrides) = approval::allowance_override(
+            provider.clone(),
+            plan.from_token,
+            plan.recipient,
+            plan.router,
+        )
+        .await
+        else {
+            return Ok((None, None));
+        };
+        match Self::estimate_swap_gas(provider, quote, plan, Some(overrides.clone())).await {
+            Ok(estimate) => Ok((Some(estimate), Some(overrides))),
+            Err(e) if e.message.contains("revert") => Err(e),
+            Err(e) => {
+                tracing::debug!(
+                    "Estimating the swap with a state override failed, using an approximate gas: {}",
+                    e.message
+                );
+                Ok((None, None))
+            }
+        }
+    }
+
+    /// 无法调用 eth_estimateGas 时的近似 gas：V3 为 Quoter 的 gasEstimate，V2 按每一跳的固定开销，再加路由合约本身的开销
+    fn approximate_swap_gas(quote: &SwapQuote) -> u64 {
+        let pools = match quote.protocol {
+            SwapProtocol::V2 => V2_POOL_SWAP_GAS * quote.route.fees.len() as u64,
+            _ => quote.route.gas_estimate.saturating_to::<u64>(),
+        };
+        pools + ROUTER_GAS_OVERHEAD
+    }
+
+    /// 对交换交易调用 eth_estimateGas，dry_run 时可由 overrides 覆盖授权额度
+    async fn estimate_swap_gas<P: Provider>(
+        provider: P,
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        overrides: Option<StateOverride>,
+    ) -> Result<u64, McpError> {
+        let call = Self::swap_call(quote, plan);
+        let tx = TransactionRequest::default()
+            .with_from(plan.recipient)
+            .with_to(call.to)
+            .with_input(call.input)
+            .with_value(call.value);
+        provider
+            .estimate_gas(tx)
+            .overrides_opt(overrides)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to estimate swap gas: {}", e), None)
+            })
+    }
+
+    /// 签名并广播交换交易，等待回执
+    async fn send_swap<P: Provider>(
+        provider: P,
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        gas_limit: u64,
+        fees: &FeeEstimate,
+    ) -> Result<TransactionReceipt, McpError> {
+        let call = Self::swap_call(quote, plan);
+        let tx = TransactionRequest::default()
+            .with_from(plan.recipient)
+            .with_to(call.to)
+            .with_input(call.input)
+            .with_value(call.value)
+            .with_gas_limit(gas_limit)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+
+        provider
+            .send_transaction(tx)
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to send swap: {}", e), None))?
+            .get_receipt()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get swap receipt: {}", e), None)
+            })
+    }
+
+    fn v3_single_params(
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        recipient: Address,
+    ) -> ISwapRouter::ExactInputSingleParams {
+        ISwapRouter::ExactInputSingleParams {
+            tokenIn: plan.from_token,
+            tokenOut: plan.to_token,
+            fee: U24::from(quote.route.fees[0]),
+            recipient,
+            amountIn: plan.amount_in,
+            deadline: plan.deadline,
+            amountOutMinimum: plan.amount_out,
+            sqrtPriceLimitX96: U160::ZERO,
+        }
+    }
+
+    fn v3_multi_hop_params(
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        recipient: Address,
+    ) -> ISwapRouter::ExactInputParams {
+        ISwapRouter::ExactInputParams {
+            path: quote.route.encode_path(),
+            recipient,
+            deadline: plan.deadline,
+            amountIn: plan.amount_in,
+            amountOutMinimum: plan.amount_out,
+        }
+    }
+
+    fn v3_exact_output_params(
+        quote: &SwapQuote,
+        plan: &SwapPlan,
+        recipient: Address,
+    ) -> ISwapRouter::ExactOutputSingleParams {
+        ISwapRouter::ExactOutputSingleParams {
+            tokenIn: plan.from_token,
+            tokenOut: plan.to_token,
+            fee: U24::from(quote.route.fees[0]),
+            recipient,
+            deadline: plan.deadline,
+            amountOut: plan.amount_out,
+            amountInMaximum: plan.amount_in,
+            sqrtPriceLimitX96: U160::ZERO,
+        }
     }
 }
@@ -0,0 +1,249 @@
+use crate::models::{
+    GetTransactionRequest, GetTransactionResponse, MetaData, TransactionEvent, TransactionStatus,
+};
+use crate::models::{IUniswapV2Pair, IUniswapV3Pool, SwapProtocol, IERC20};
+use crate::services::block_resolver::is_missing_state_error;
+use crate::services::receipt_events::{self, PoolSwap, ReceiptEvent};
+use crate::services::{gas, ChainContext};
+use alloy::consensus::Transaction as _;
+use alloy::eips::BlockId;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, TxHash, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::Transaction;
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::{model::*, ErrorData as McpError};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct TransactionHandler {
+    chain: Arc<ChainContext>,
+}
+
+impl TransactionHandler {
+    pub fn new(chain: Arc<ChainContext>) -> Self {
+        Self { chain }
+    }
+
+    pub async fn handle_get_transaction(
+        &self,
+        Parameters(GetTransactionRequest { tx_hash, chain: _ }): Parameters<GetTransactionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let hash: TxHash = tx_hash.trim().parse().map_err(|e| {
+            McpError::invalid_params(
+                format!("Invalid transaction hash '{}': {}", tx_hash, e),
+                None,
+            )
+        })?;
+        let provider = self.chain.provider.clone();
+        let tx = provider
+            .get_transaction_by_hash(hash)
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get transaction: {}", e), None)
+            })?
+            .ok_or_else(|| {
+                McpError::invalid_params(
+                    format!("Transaction {} not found on {}", hash, self.chain.name),
+                    None,
+                )
+            })?;
+        let receipt = provider.get_transaction_receipt(hash).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get transaction receipt: {}", e), None)
+        })?;
+
+        let native_symbol = self.chain.addresses.native_symbol;
+        let native = |value: U256| MetaData {
+            value,
+            decimals: 18,
+            symbol: native_symbol.to_string(),
+        };
+        let mut response = GetTransactionResponse {
+            tx_hash: hash.to_string(),
+            status: TransactionStatus::Pending,
+            from: tx.inner.signer().to_string(),
+            to: tx.to().map(|to| to.to_string()),
+            value: native(tx.value()),
+            nonce: tx.nonce(),
+            gas_limit: tx.gas_limit(),
+            block_number: None,
+            confirmations: None,
+            gas_used: None,
+            effective_gas_price: None,
+            fee: None,
+            events: Vec::new(),
+            revert_reason: None,
+        };
+        // 没有回执说明交易还在交易池中
+        let Some(receipt) = receipt else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                response.to_string(),
+            )]));
+        };
+
+        let latest = provider.get_block_number().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get block number: {}", e), None)
+        })?;
+        response.block_number = receipt.block_number;
+        // 备用节点可能落后于回执所在区块，至少算 1 个确认
+        response.confirmations = receipt
+            .block_number
+            .map(|block_number| latest.saturating_sub(block_number) + 1);
+        response.gas_used = Some(receipt.gas_used);
+        response.effective_gas_price = Some(gas::gwei(receipt.effective_gas_price));
+        response.fee = Some(native(
+            U256::from(receipt.gas_used) * U256::from(receipt.effective_gas_price),
+        ));
+        response.events = self
+            .describe_events(receipt_events::decode_events(receipt.inner.logs()))
+            .await;
+        if receipt.status() {
+            response.status = TransactionStatus::Mined;
+        } else {
+            response.status = TransactionStatus::Failed;
+            response.revert_reason = Some(
+                self.revert_reason(tx, receipt.block_number, receipt.gas_used)
+                    .await,
+            );
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+
+    /// 为事件补充代币精度和符号，同一个代币只查询一次
+    async fn describe_events(&self, events: Vec<ReceiptEvent>) -> Vec<TransactionEvent> {
+        let mut tokens: HashMap<Address, (u8, String)> = HashMap::new();
+        let mut described = Vec::with_capacity(events.len());
+        for event in events {
+            match event {
+                ReceiptEvent::Transfer {
+                    token,
+                    from,
+                    to,
+                    value,
+                } => {
+                    let (decimals, symbol) = self.token_metadata(&mut tokens, token).await;
+                    described.push(TransactionEvent::Transfer {
+                        token: token.to_string(),
+                        from: from.to_string(),
+                        to: to.to_string(),
+                        amount: MetaData {
+                            value,
+                            decimals,
+                            symbol,
+                        },
+                    });
+                }
+                ReceiptEvent::Swap(swap) => {
+                    let Some((token0, token1)) = self.pool_tokens(&swap).await else {
+                        continue;
+                    };
+                    let (token_in, token_out) = if swap.zero_for_one {
+                        (token0, token1)
+                    } else {
+                        (token1, token0)
+                    };
+                    let (in_decimals, in_symbol) = self.token_metadata(&mut tokens, token_in).await;
+                    let (out_decimals, out_symbol) =
+                        self.token_metadata(&mut tokens, token_out).await;
+                    described.push(TransactionEvent::Swap {
+                        protocol: swap.protocol,
+                        pool: swap.pool.to_string(),
+                        sender: swap.sender.to_string(),
+                        recipient: swap.recipient.to_string(),
+                        amount_in: MetaData {
+                            value: swap.amount_in,
+                            decimals: in_decimals,
+                            symbol: in_symbol,
+                        },
+                        amount_out: MetaData {
+                            value: swap.amount_out,
+                            decimals: out_decimals,
+                            symbol: out_symbol,
+                        },
+                    });
+                }
+            }
+        }
+        described
+    }
+
+    /// 读取池子的 token0/token1，失败时（同名事件但不是 Uniswap 池子）跳过该事件
+    async fn pool_tokens(&self, swap: &PoolSwap) -> Option<(Address, Address)> {
+        let provider = self.chain.provider.clone();
+        match swap.protocol {
+            SwapProtocol::V2 => {
+                let pair = IUniswapV2Pair::new(swap.pool, provider);
+                Some((
+                    pair.token0().call().await.ok()?,
+                    pair.token1().call().await.ok()?,
+                ))
+            }
+            _ => {
+                let pool = IUniswapV3Pool::new(swap.pool, provider);
+                Some((
+                    pool.token0().call().await.ok()?,
+                    pool.token1().call().await.ok()?,
+                ))
+            }
+        }
+    }
+
+    /// 读取代币精度和符号，不是标准 ERC20 时按原始数量显示并用地址代替符号
+    async fn token_metadata(
+        &self,
+        cache: &mut HashMap<Address, (u8, String)>,
+        token: Address,
+    ) -> (u8, String) {
+        if let Some(metadata) = cache.get(&token) {
+            return metadata.clone();
+        }
+        let erc20 = IERC20::new(token, self.chain.provider.clone());
+        let metadata = match (erc20.decimals().call().await, erc20.symbol().call().await) {
+            (Ok(decimals), Ok(symbol)) => (decimals, symbol),
+            _ => (0, token.to_string()),
+        };
+        cache.insert(token, metadata.clone());
+        metadata
+    }
+
+    /// 在父区块状态上重放失败的交易以取得 revert 原因，同一区块中排在前面的交易不计入，结果仅供参考
+    async fn revert_reason(
+        &self,
+        tx: Transaction,
+        block_number: Option<u64>,
+        gas_used: u64,
+    ) -> String {
+        if gas_used >= tx.gas_limit() {
+            return "out of gas (used the entire gas limit)".to_string();
+        }
+        let from = tx.inner.signer();
+        let mut request = tx.into_request().with_from(from);
+        // 只关心执行结果，去掉 nonce 和费用字段，避免节点按父区块的账户状态拒绝调用
+        request.nonce = None;
+        request.gas_price = None;
+        request.max_fee_per_gas = None;
+        request.max_priority_fee_per_gas = None;
+        let block = block_number
+            .map(|block_number| BlockId::number(block_number.saturating_sub(1)))
+            .unwrap_or_else(BlockId::latest);
+
+        match self.chain.provider.call(request).block(block).await {
+            Ok(_) => "unknown, the transaction does not revert when replayed on the parent block"
+                .to_string(),
+            Err(e) if is_missing_state_error(&e.to_string()) => {
+                "unknown, replaying this transaction requires an archive node".to_string()
+            }
+            Err(e) => match e.as_error_resp() {
+                Some(payload) => payload
+                    .as_revert_data()
+                    .and_then(|data| receipt_events::revert_reason(&data))
+                    .unwrap_or_else(|| payload.message.to_string()),
+                None => format!("unknown, failed to replay the transaction: {}", e),
+            },
+        }
+    }
+}
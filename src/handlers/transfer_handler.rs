@@ -0,0 +1,213 @@
+use crate::config::PolicyConfig;
+use crate::handlers::PriceHandler;
+use crate::models::IERC20;
+use crate::models::{MetaData, TransferExecution, TransferRequest, TransferResponse};
+use crate::services::{gas, ChainContext};
+use alloy::network::TransactionBuilder;
+use alloy::primitives::utils::parse_units;
+use alloy::primitives::U256;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::SolCall;
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::{model::*, ErrorData as McpError};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct TransferHandler {
+    private_key: String,
+    chain: Arc<ChainContext>,
+    policy: PolicyConfig,
+}
+
+impl TransferHandler {
+    pub fn new(private_key: String, chain: Arc<ChainContext>) -> Self {
+        Self {
+            private_key,
+            chain,
+            policy: PolicyConfig::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: PolicyConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub async fn handle_transfer(
+        &self,
+        Parameters(TransferRequest {
+            to,
+            token,
+            amount,
+            dry_run,
+            fee_speed,
+            chain: _,
+        }): Parameters<TransferRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let dry_run = dry_run.unwrap_or(false);
+        if !self.policy.allow_broadcast && !dry_run {
+            return Err(McpError::invalid_params(
+                "Broadcasting transfers is disabled by policy, set dry_run to true",
+                None,
+            ));
+        }
+
+        let signer: PrivateKeySigner = self
+            .private_key
+            .parse()
+            .map_err(|e| McpError::invalid_params(format!("Invalid private key: {}", e), None))?;
+        let sender = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(signer)
+            .connect_provider(self.chain.provider.clone());
+
+        let recipient = self.chain.resolve_address(&to, "recipient").await?;
+        if recipient.address.is_zero() {
+            return Err(McpError::invalid_params(
+                "Cannot transfer to the zero address",
+                None,
+            ));
+        }
+
+        // 原生代币直接转账，其余按 ERC20 调用 transfer
+        let native_symbol = self.chain.addresses.native_symbol;
+        let native = token.trim().eq_ignore_ascii_case(native_symbol);
+        let token_address = if native {
+            None
+        } else {
+            Some(self.chain.resolve_token(&token)?)
+        };
+        let (decimals, symbol) = match token_address {
+            None => (18, native_symbol.to_string()),
+            Some(token_address) => {
+                let erc20 = IERC20::new(token_address, provider.clone());
+                let decimals = erc20.decimals().call().await.map_err(|e| {
+                    McpError::internal_error(format!("Failed to get token decimals: {}", e), None)
+                })?;
+                let symbol = erc20.symbol().call().await.map_err(|e| {
+                    McpError::internal_error(format!("Failed to get token symbol: {}", e), None)
+                })?;
+                (decimals, symbol)
+            }
+        };
+        let amount_raw: U256 = parse_units(amount.to_string().as_str(), decimals)
+            .map_err(|e| {
+                McpError::invalid_params(format!("Invalid amount '{}': {}", amount, e), None)
+            })?
+            .into();
+        if amount_raw == U256::ZERO {
+            return Err(McpError::invalid_params("Amount cannot be zero", None));
+        }
+        let metadata = |value: U256, decimals: u8, symbol: &str| MetaData {
+            value,
+            decimals,
+            symbol: symbol.to_string(),
+        };
+
+        // 先检查代币余额，避免估算 gas 时只得到笼统的 revert
+        if let Some(token_address) = token_address {
+            let balance = IERC20::new(token_address, provider.clone())
+                .balanceOf(sender)
+                .call()
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to get token balance: {}", e), None)
+                })?;
+            if balance < amount_raw {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "Insufficient balance: have {}, need {}",
+                        metadata(balance, decimals, &symbol),
+                        metadata(amount_raw, decimals, &symbol)
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        let tx = match token_address {
+            None => TransactionRequest::default()
+                .with_from(sender)
+                .with_to(recipient.address)
+                .with_value(amount_raw),
+            Some(token_address) => TransactionRequest::default()
+                .with_from(sender)
+                .with_to(token_address)
+                .with_input(
+                    IERC20::transferCall {
+                        to: recipient.address,
+                        amount: amount_raw,
+                    }
+                    .abi_encode(),
+                ),
+        };
+        let gas_estimate = provider.estimate_gas(tx.clone()).await.map_err(|e| {
+            McpError::internal_error(format!("Failed to estimate gas: {}", e), None)
+        })?;
+        let gas_limit = gas::gas_limit_with_buffer(gas_estimate);
+
+        // 按 maxFeePerGas 计算最坏情况下的 gas 费用，原生代币余额需同时覆盖转账数量
+        let speed = fee_speed.unwrap_or_default();
+        let fees = gas::estimate_fees(&provider, speed).await?;
+        let value = if native { amount_raw } else { U256::ZERO };
+        gas::check_native_balance(
+            &provider,
+            sender,
+            value,
+            fees.max_cost(gas_limit),
+            native_symbol,
+        )
+        .await?;
+
+        let native_usd = PriceHandler::new(self.chain.clone())
+            .usd_price(self.chain.addresses.wrapped_native)
+            .await
+            .ok();
+        let mut response = TransferResponse {
+            from: sender.to_string(),
+            to: recipient.address.to_string(),
+            ens_name: recipient.ens_name,
+            amount: metadata(amount_raw, decimals, &symbol),
+            gas: gas::gas_report(
+                speed,
+                gas_estimate,
+                gas_limit,
+                &fees,
+                native_symbol,
+                native_usd.as_ref(),
+            ),
+            execution: None,
+        };
+        if dry_run {
+            return Ok(CallToolResult::success(vec![Content::text(
+                response.to_string(),
+            )]));
+        }
+
+        // 签名并广播，nonce 由钱包 provider 填充
+        let receipt = provider
+            .send_transaction(
+                tx.with_gas_limit(gas_limit)
+                    .with_max_fee_per_gas(fees.max_fee_per_gas)
+                    .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas),
+            )
+            .await
+            .map_err(|e| McpError::internal_error(format!("Failed to send transfer: {}", e), None))?
+            .get_receipt()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get transfer receipt: {}", e), None)
+            })?;
+        response.execution = Some(TransferExecution {
+            tx_hash: receipt.transaction_hash.to_string(),
+            status: receipt.status(),
+            block_number: receipt.block_number,
+            gas_used: receipt.gas_used,
+        });
+        Ok(CallToolResult::success(vec![Content::text(
+            response.to_string(),
+        )]))
+    }
+}
@@ -1,7 +1,10 @@
+pub mod auth;
+pub mod config;
 pub mod handlers;
 pub mod models;
 pub mod server;
 pub mod services;
+pub mod transport;
 
 // 重新导出主要的公共接口
 pub use models::*;
@@ -1,11 +1,14 @@
+mod auth;
+mod config;
 mod handlers;
 mod models;
 mod server;
 mod services;
+mod transport;
+use crate::config::Config;
 use crate::server::McpServer;
+use crate::transport::ServeOptions;
 use anyhow::Result;
-use rmcp::transport::stdio;
-use rmcp::ServiceExt;
 use std::env;
 use tracing_subscriber::{
     EnvFilter, {self},
@@ -20,19 +23,13 @@ async fn main() -> Result<()> {
         .with_ansi(false)
         .init();
 
-    tracing::info!("Starting MCP server");
+    let options = ServeOptions::from_args(env::args().skip(1))?;
+    tracing::info!("Starting MCP server over {}", options.transport);
 
-    // dotenvy::dotenv()?;
-    let rpc_url = env::var("ETH_RPC_URL").unwrap_or_else(|_| "default_key".to_string());
-    let private_key = env::var("PRIVATE_KEY").unwrap_or_else(|_| "default_key".to_string());
-    // Create an instance of our counter router
-    let service = McpServer::new(private_key, rpc_url)
-        .serve(stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("serving error: {:?}", e);
-        })?;
+    // 加载 .env 和配置文件，启动前确认 RPC 可达且链 ID 一致
+    let mut config = Config::load(options.config.as_deref())?;
+    config.check_networks().await?;
 
-    service.waiting().await?;
-    Ok(())
+    let server = McpServer::from_config(&config).await?;
+    transport::serve(server, &options, config.auth_config()).await
 }
@@ -0,0 +1,283 @@
+use super::constants::{ENS_REGISTRY, MULTICALL3};
+use alloy::primitives::{address, Address};
+
+/// 单条链上使用的代币和合约地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainAddresses {
+    pub chain_id: u64,
+    pub name: &'static str,
+    pub native_symbol: &'static str, // 原生代币符号，swap 中用它表示原生代币
+    pub wrapped_native: Address,     // 包装原生代币的合约（WETH / WPOL），用于 wrap/unwrap
+    pub weth: Address,               // 以 ETH 计价和经 WETH 路由时使用
+    pub usdc: Address,               // 以 USD 计价时使用
+    pub usdt: Option<Address>,
+    pub dai: Option<Address>,
+    pub wbtc: Option<Address>,
+    pub v3_factory: Address,
+    pub quoter_v2: Address,
+    pub multicall3: Address,                      // Multicall3，用于批量查询
+    pub swap_router: Option<Address>, // SwapRouter（非 SwapRouter02），未部署时不支持 V3 兑换
+    pub v2_router: Option<Address>,   // V2 Router02，未部署时不支持 V2 兑换
+    pub feed_registry: Option<Address>, // Chainlink Feed Registry，仅主网
+    pub ens_registry: Option<Address>, // ENS Registry，L2 上的 ENS 名称通过主网解析
+    pub usd_feeds: &'static [(Address, Address)], // 内置的 Chainlink USD 喂价（代币, 聚合器）
+}
+
+pub const MAINNET: ChainAddresses = ChainAddresses {
+    chain_id: 1,
+    name: "mainnet",
+    native_symbol: "ETH",
+    wrapped_native: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+    weth: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+    usdc: address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+    usdt: Some(address!("0xdAC17F958D2ee523a2206206994597C13D831ec7")),
+    dai: Some(address!("0x6B175474E89094C44Da98b954EedeAC495271d0F")),
+    wbtc: Some(address!("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599")),
+    v3_factory: address!("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+    quoter_v2: address!("0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    multicall3: MULTICALL3,
+    swap_router: Some(address!("0xE592427A0AEce92De3Edee1F18E0157C05861564")),
+    v2_router: Some(address!("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D")),
+    feed_registry: Some(address!("0x47Fb2585D2C56Fe188D0E6ec628a38b74fCeeeDf")),
+    ens_registry: Some(ENS_REGISTRY),
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            address!("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+        ),
+        // BTC / USD
+        (
+            address!("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"),
+            address!("0xF4030086522a5bEEa4988F8cA5B36dbC97BeE88c"),
+        ),
+        // USDC / USD
+        (
+            address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            address!("0x8fFfFfd4AfB6115b954Bd326cbe7B4BA576818f6"),
+        ),
+        // USDT / USD
+        (
+            address!("0xdAC17F958D2ee523a2206206994597C13D831ec7"),
+            address!("0x3E7d1eAB13ad0104d2750B8863b489D65364e32D"),
+        ),
+        // DAI / USD
+        (
+            address!("0x6B175474E89094C44Da98b954EedeAC495271d0F"),
+            address!("0xAed0c38402a5d19df6E4c03299E5f2A7b3aA4a9D"),
+        ),
+    ],
+};
+
+pub const ARBITRUM: ChainAddresses = ChainAddresses {
+    chain_id: 42161,
+    name: "arbitrum",
+    native_symbol: "ETH",
+    wrapped_native: address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+    weth: address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+    usdc: address!("0xaf88d065e77c8cC2239327C5EDb3A432268e5831"),
+    usdt: Some(address!("0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9")),
+    dai: Some(address!("0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1")),
+    wbtc: Some(address!("0x2f2a2543B76A4166549F7aaB2e75Bef0aefC5B0f")),
+    v3_factory: address!("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+    quoter_v2: address!("0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    multicall3: MULTICALL3,
+    swap_router: Some(address!("0xE592427A0AEce92De3Edee1F18E0157C05861564")),
+    v2_router: Some(address!("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+    feed_registry: None,
+    ens_registry: None,
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"),
+            address!("0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612"),
+        ),
+    ],
+};
+
+pub const OPTIMISM: ChainAddresses = ChainAddresses {
+    chain_id: 10,
+    name: "optimism",
+    native_symbol: "ETH",
+    wrapped_native: address!("0x4200000000000000000000000000000000000006"),
+    weth: address!("0x4200000000000000000000000000000000000006"),
+    usdc: address!("0x0b2C639c533813f4Aa9D7837cAf62653d097Ff85"),
+    usdt: Some(address!("0x94b008aA00579c1307B0EF2c499aD98a8ce58e58")),
+    dai: Some(address!("0xDA10009cBd5D07dd0CeCc66161FC93D7c9000da1")),
+    wbtc: Some(address!("0x68f180fcCe6836688e9084f035309E29Bf0A2095")),
+    v3_factory: address!("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+    quoter_v2: address!("0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    multicall3: MULTICALL3,
+    swap_router: Some(address!("0xE592427A0AEce92De3Edee1F18E0157C05861564")),
+    v2_router: Some(address!("0x4A7b5Da61326A6379179b40d00F57E5bbDC962c2")),
+    feed_registry: None,
+    ens_registry: None,
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0x4200000000000000000000000000000000000006"),
+            address!("0x13e3Ee699D1909E989722E753853AE30b17e08c5"),
+        ),
+    ],
+};
+
+pub const BASE: ChainAddresses = ChainAddresses {
+    chain_id: 8453,
+    name: "base",
+    native_symbol: "ETH",
+    wrapped_native: address!("0x4200000000000000000000000000000000000006"),
+    weth: address!("0x4200000000000000000000000000000000000006"),
+    usdc: address!("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+    usdt: None,
+    dai: Some(address!("0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb")),
+    wbtc: None,
+    v3_factory: address!("0x33128a8fC17869897dcE68Ed026d694621f6FDfD"),
+    quoter_v2: address!("0x3d4e44Eb1374240CE5F1B871ab261CD16335B76a"),
+    multicall3: MULTICALL3,
+    swap_router: None, // 只部署了接口不同的 SwapRouter02
+    v2_router: Some(address!("0x4752ba5DBc23f44D87826276BF6Fd6b1C372aD24")),
+    feed_registry: None,
+    ens_registry: None,
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0x4200000000000000000000000000000000000006"),
+            address!("0x71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70"),
+        ),
+    ],
+};
+
+pub const POLYGON: ChainAddresses = ChainAddresses {
+    chain_id: 137,
+    name: "polygon",
+    native_symbol: "POL",
+    wrapped_native: address!("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270"),
+    weth: address!("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"),
+    usdc: address!("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359"),
+    usdt: Some(address!("0xc2132D05D31c914a87C6611C10748AEb04B58e8F")),
+    dai: Some(address!("0x8f3Cf7ad23Cd3CaDbD9735AFf958023239c6A063")),
+    wbtc: Some(address!("0x1BFD67037B42Cf73acF2047067bd4F2C47D9BfD6")),
+    v3_factory: address!("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+    quoter_v2: address!("0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    multicall3: MULTICALL3,
+    swap_router: Some(address!("0xE592427A0AEce92De3Edee1F18E0157C05861564")),
+    v2_router: Some(address!("0xedf6066a2b290C185783862C7F4776A2C8077AD1")),
+    feed_registry: None,
+    ens_registry: None,
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"),
+            address!("0xF9680D99D6C9589e2a93a78A04A279e509205945"),
+        ),
+        // MATIC / USD
+        (
+            address!("0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270"),
+            address!("0xAB594600376Ec9fD91F8e885dADF0CE036862dE0"),
+        ),
+    ],
+};
+
+pub const SEPOLIA: ChainAddresses = ChainAddresses {
+    chain_id: 11155111,
+    name: "sepolia",
+    native_symbol: "ETH",
+    wrapped_native: address!("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
+    weth: address!("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
+    usdc: address!("0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"),
+    usdt: None,
+    dai: None,
+    wbtc: None,
+    v3_factory: address!("0x0227628f3F023bb0B980b67D528571c95c6DaC1c"),
+    quoter_v2: address!("0xEd1f6473345F45b75F8179591dd5bA1888cf2FB3"),
+    multicall3: MULTICALL3,
+    swap_router: None, // 只部署了接口不同的 SwapRouter02
+    v2_router: None,
+    feed_registry: None,
+    ens_registry: Some(ENS_REGISTRY),
+    usd_feeds: &[
+        // ETH / USD
+        (
+            address!("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
+            address!("0x694AA1769357215DE4FAC081bf1f309aDC325306"),
+        ),
+    ],
+};
+
+/// 内置地址表的链
+pub static CHAINS: [ChainAddresses; 6] = [MAINNET, ARBITRUM, OPTIMISM, BASE, POLYGON, SEPOLIA];
+
+/// 按链 ID 查找内置地址表
+pub fn chain_by_id(chain_id: u64) -> Option<&'static ChainAddresses> {
+    CHAINS.iter().find(|chain| chain.chain_id == chain_id)
+}
+
+/// 按名称查找内置地址表，"ethereum" 为 "mainnet" 的别名
+pub fn chain_by_name(name: &str) -> Option<&'static ChainAddresses> {
+    let name = if name.eq_ignore_ascii_case("ethereum") {
+        "mainnet"
+    } else {
+        name
+    };
+    CHAINS
+        .iter()
+        .find(|chain| chain.name.eq_ignore_ascii_case(name))
+}
+
+impl ChainAddresses {
+    /// 多跳路由时尝试的中间代币
+    pub fn hub_tokens(&self) -> Vec<Address> {
+        let mut hubs = vec![self.weth, self.usdc];
+        hubs.extend([self.usdt, self.dai].into_iter().flatten());
+        if self.wrapped_native != self.weth {
+            hubs.push(self.wrapped_native);
+        }
+        hubs
+    }
+
+    /// 可以直接按符号引用的常用代币
+    pub fn known_tokens(&self) -> Vec<(&'static str, Address)> {
+        let mut tokens = vec![("WETH", self.weth), ("USDC", self.usdc)];
+        tokens.extend(
+            [("USDT", self.usdt), ("DAI", self.dai), ("WBTC", self.wbtc)]
+                .into_iter()
+                .filter_map(|(symbol, address)| address.map(|address| (symbol, address))),
+        );
+        if self.wrapped_native != self.weth {
+            tokens.push(("WPOL", self.wrapped_native));
+        }
+        tokens
+    }
+
+    /// 内置的 Chainlink USD 喂价
+    pub fn usd_feed(&self, token: Address) -> Option<Address> {
+        self.usd_feeds
+            .iter()
+            .find(|(base, _)| *base == token)
+            .map(|(_, feed)| *feed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_lookup() {
+        assert_eq!(chain_by_id(1), Some(&MAINNET));
+        assert_eq!(chain_by_name("Ethereum"), Some(&MAINNET));
+        assert_eq!(chain_by_name("base").map(|c| c.chain_id), Some(8453));
+        assert_eq!(chain_by_id(56), None);
+    }
+
+    #[test]
+    fn test_hub_tokens() {
+        assert_eq!(MAINNET.hub_tokens().len(), 4);
+        assert_eq!(
+            BASE.hub_tokens(),
+            vec![BASE.weth, BASE.usdc, BASE.dai.unwrap()]
+        );
+        // Polygon 上 WPOL 与 WETH 不同，也作为中间代币
+        assert!(POLYGON.hub_tokens().contains(&POLYGON.wrapped_native));
+    }
+}
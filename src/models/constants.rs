@@ -1,6 +1,18 @@
 use alloy::primitives::{address, Address};
 
-pub const USDT_ADDRESS: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
-pub const QUOTER_ADDRESS: Address = address!("0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6"); // V3 Quoter
-pub const QUOTERV2_ADDRESS: Address = address!("0x61fFE014bA17989E743c5F6cB21bF9697530B21e"); // V3 Quoter V2
-pub const ROUTER_ADDRESS: Address = address!("E592427A0AEce92De3Edee1F18E0157C05861564"); // V3 Router
+/// 代表 ETH 的代币参数，链上按该链的 WETH 报价和路由
+pub const NATIVE_ETH: &str = "ETH";
+
+/// Multicall3 在各链上的统一部署地址
+pub const MULTICALL3: Address = address!("0xcA11bde05977b3631167028862bE2a173976CA11");
+
+/// ENS Registry 在主网和测试网上的部署地址
+pub const ENS_REGISTRY: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// 以 USD 计价时使用的报价代币
+pub const USD_QUOTE: &str = "USD";
+
+/// Chainlink Feed Registry 使用的计价单位地址
+pub const DENOMINATION_USD: Address = address!("0x0000000000000000000000000000000000000348");
+pub const DENOMINATION_ETH: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+pub const DENOMINATION_BTC: Address = address!("0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB");
@@ -1,8 +1,10 @@
+pub mod chains;
 pub mod constants;
 pub mod sol;
 pub mod types;
 
 // 重新导出主要的公共接口
+pub use chains::*;
 pub use constants::*;
 pub use sol::*;
-pub use types::*;
\ No newline at end of file
+pub use types::*;
@@ -1,5 +1,5 @@
 use alloy::primitives::utils::format_units;
-use alloy::primitives::U256;
+use alloy::primitives::{Address, U256};
 use rmcp::schemars;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
@@ -7,44 +7,338 @@ use std::fmt::{self, Display, Formatter};
 /// 1. 余额查询
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct GetBalanceRequest {
-    pub wallet_address: String,
-    pub token_address: Option<String>, // None 表示查 ETH
+    pub wallet_address: String,        // 地址或 ENS 名称
+    pub token_address: Option<String>, // 地址、符号或名称，None 表示查原生代币
+    pub block: Option<String>, // 区块号、标签（finalized 等）或 ISO 8601 时间戳，None 表示最新区块
+    pub chain: Option<String>, // 链 ID 或网络名，None 表示默认网络
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetBalanceResponse {
     pub price: MetaData,
+    pub wallet: String,            // 解析后的钱包地址
+    pub ens_name: Option<String>,  // 输入的 ENS 名称或反向解析出的主名称
+    pub block_number: Option<u64>, // 指定 block 时实际查询的区块
 }
 
 /// 2. 价格查询
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct GetTokenPriceRequest {
-    pub token: String, // 地址
-    pub fee: Option<u32>,
+    pub token: String,                 // 地址、符号或名称
+    pub fee: Option<u32>,              // None 表示自动探测最优 V3 费率档位
+    pub source: Option<PriceSource>,   // 默认 uniswap
+    pub quote_token: Option<String>,   // 计价代币：地址、符号、"ETH" 或 "USD"（默认）
+    pub max_age_secs: Option<u64>, // Chainlink 喂价允许的最大延迟，默认 75 分钟，已知每日更新的喂价（主网 USDC/USDT）为 25 小时
+    pub twap_window_secs: Option<u32>, // twap 来源的时间窗口，默认 1800 秒
+    pub chain: Option<String>,     // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 价格来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSource {
+    Uniswap,
+    Chainlink,
+    Auto, // 优先 Chainlink，无喂价或喂价过期时回退到 Uniswap
+    Spot, // 直接读取池子 slot0 的即时价格
+    Twap, // 通过池子 observe() 计算时间加权平均价格
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetTokenPriceResponse {
     pub price: MetaData,
+    pub source: PriceSource,      // 实际使用的价格来源
+    pub path: Vec<String>,        // Uniswap 报价经过的代币，Chainlink 时为空
+    pub fees: Vec<u32>,           // 每一跳实际使用的 V3 费率档位
+    pub updated_at: Option<u64>,  // Chainlink 喂价更新时间（unix 秒）
+    pub round_id: Option<String>, // Chainlink 喂价轮次
+    pub window_secs: Option<u32>, // TWAP 时间窗口
 }
 
 /// 3. 兑换模拟
 #[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct SwapTokensRequest {
-    pub from_token: String, // 地址或符号
+    pub from_token: String, // 地址、符号或名称，"ETH"（Polygon 上为 "POL"）表示原生代币
     pub to_token: String,
-    pub amount: f64,
+    pub amount: f64, // exact_in 时为 from_token 数量，exact_out 时为 to_token 数量
     pub slippage_pct: u128,
-    pub fee: Option<u32>,
+    pub fee: Option<u32>,               // None 表示自动探测最优 V3 费率档位
+    pub dry_run: Option<bool>,          // true 表示仅模拟，不广播交易
+    pub protocol: Option<SwapProtocol>, // 默认 v3
+    pub side: Option<SwapSide>,         // 默认 exact_in
+    pub max_price_deviation_bps: Option<u32>, // 设置后广播前检查价格共识，偏差超限时拒绝执行
+    pub fee_speed: Option<FeeSpeed>,    // 手续费档位，默认 normal
+    pub chain: Option<String>,          // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 交换使用的 Uniswap 版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapProtocol {
+    V2,
+    V3,
+    Auto, // 两边都报价，选择到手数量更多的一方
+}
+
+/// 交换方向：固定输入数量或固定输出数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapSide {
+    ExactIn,
+    ExactOut, // 仅支持 V3 单池
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwapTokensResponse {
-    pub estimated_out: MetaData, // 预计到手数量（已格式化）
-    pub gas_price: String,       // Gwei 字符串
+    pub protocol: SwapProtocol, // 实际使用的交易场所
+    pub side: SwapSide,
+    pub path: Vec<String>,               // 兑换路径上依次经过的代币地址
+    pub fees: Vec<u32>,                  // 每一跳使用的费率档位
+    pub estimated_out: MetaData,         // 预计到手数量（已格式化）
+    pub expected_in: Option<MetaData>,   // exact_out 时的预计花费
+    pub max_in: Option<MetaData>,        // exact_out 时按滑点计算的最大花费
+    pub gas: GasReport,                  // 交换交易的 gas 和手续费预估
+    pub approval_gas: Option<GasReport>, // 授权不足时 approve 交易的预估
+    pub total_cost: MetaData,            // approve 和交换交易的预计手续费合计
+    pub total_cost_usd: Option<MetaData>,
+    pub execution: Option<SwapExecution>, // None 表示仅模拟
+}
+
+/// 链上实际执行结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwapExecution {
+    pub tx_hash: String,
+    pub block_number: Option<u64>,
+    pub amount_in: MetaData,  // 从 Transfer 日志解析出的实际花费
+    pub amount_out: MetaData, // 从 Transfer 日志解析出的实际到手数量
+    pub gas_used: u64,
+    pub gas_price: String, // 实际成交的 effective gas price（Gwei）
+    pub approval_tx_hash: Option<String>,
+    pub fee: MetaData, // approve 和交换交易实际支付的手续费合计
+}
+
+/// 手续费档位，分别取最近区块优先费的 10/50/90 百分位
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+/// 状态变更交易的 gas 和 EIP-1559 手续费预估
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GasReport {
+    pub speed: FeeSpeed,
+    pub gas_estimate: u64,          // eth_estimateGas 的结果
+    pub approximate: bool, // 为 true 时 gas_estimate 是按报价估算的近似值，而不是 eth_estimateGas 的结果
+    pub gas_limit: u64,    // 交易实际使用的上限，在预估值上留有余量
+    pub base_fee_gwei: String, // 下一个区块的 base fee
+    pub max_fee_gwei: String, // maxFeePerGas
+    pub priority_fee_gwei: String, // maxPriorityFeePerGas
+    pub cost: MetaData,    // gas_estimate × (base fee + 优先费)，以原生代币计
+    pub cost_usd: Option<MetaData>, // 无法获取原生代币价格时为空
+}
+
+/// 4. 价格共识
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetPriceConsensusRequest {
+    pub token: String,                  // 地址、符号或名称
+    pub fee: Option<u32>,               // Uniswap 报价和 TWAP 使用的费率档位，None 表示自动选择
+    pub max_age_secs: Option<u64>,      // Chainlink 喂价允许的最大延迟，默认值同 get_token_price
+    pub twap_window_secs: Option<u32>,  // TWAP 时间窗口，默认 1800 秒
+    pub max_deviation_bps: Option<u32>, // 偏差阈值，默认 200 基点
+    pub chain: Option<String>,          // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 单个来源的 USD 价格，获取失败时记录错误
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourcePrice {
+    pub source: PriceSource,
+    pub price: Option<MetaData>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPriceConsensusResponse {
+    pub token: String,
+    pub prices: Vec<SourcePrice>,
+    pub median: MetaData,       // 统一换算为 18 位精度
+    pub max_deviation_bps: u32, // 各来源相对中位数的最大偏差
+    pub threshold_bps: u32,
+    pub flagged: bool, // 偏差超过阈值或可用来源不足时为 true
+}
+
+/// 5. 批量余额查询
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetBalancesRequest {
+    pub wallet_addresses: Vec<String>,        // 地址或 ENS 名称
+    pub token_addresses: Option<Vec<String>>, // 地址、符号或名称，省略时只查原生代币
+    pub include_native: Option<bool>,         // 是否包含原生代币余额，默认 true
+    pub discover: Option<bool>, // 为 true 时加入从 Transfer 日志发现的代币，默认 false
+    pub chain: Option<String>,  // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 单个钱包在单个代币上的余额，查询失败时记录错误
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceCell {
+    pub token: Option<Address>, // 代币地址，None 表示原生代币
+    pub balance: Option<MetaData>,
+    pub error: Option<String>,
+}
+
+/// 余额表的一行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletBalances {
+    pub wallet: String,
+    pub ens_name: Option<String>,
+    pub balances: Vec<BalanceCell>, // 与 columns 一一对应
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetBalancesResponse {
+    pub block_number: Option<u64>, // 所有余额读取自同一个区块
+    pub columns: Vec<String>,      // 每列代币的符号，原生代币在第一列
+    pub rows: Vec<WalletBalances>,
+}
+
+/// 6. 持仓估值
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetPortfolioRequest {
+    pub wallet_address: String,               // 地址或 ENS 名称
+    pub token_addresses: Option<Vec<String>>, // 地址、符号或名称，省略时使用该链的常用代币
+    pub min_value_usd: Option<f64>,           // 低于该价值的持仓视为粉尘不返回，默认 1
+    pub discover: Option<bool>, // 为 true 时加入从 Transfer 日志发现的代币，默认 false
+    pub chain: Option<String>,  // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 单个持仓，无法定价时 value_usd 为空并记录错误
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioHolding {
+    pub token: String, // 代币地址，原生代币为其符号
+    pub amount: MetaData,
+    pub price_usd: Option<MetaData>,
+    pub value_usd: Option<MetaData>, // 统一换算为 18 位精度
+    pub share_bps: Option<u32>,      // 占已定价总价值的比例（基点）
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct GetPortfolioResponse {
+    pub wallet: String,
+    pub ens_name: Option<String>,
+    pub block_number: Option<u64>,
+    pub holdings: Vec<PortfolioHolding>, // 按价值从高到低，无法定价的排在最后
+    pub total_value_usd: MetaData,
+    pub dust_skipped: usize, // 低于 min_value_usd，或无法定价且不在已知代币表中被跳过的持仓数量
+}
+
+/// 7. 代币发现
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct DiscoverTokensRequest {
+    pub wallet_address: String,  // 地址或 ENS 名称
+    pub from_block: Option<u64>, // 默认从上次扫描到的区块继续，首次从创世区块开始
+    pub chain: Option<String>,   // 链 ID 或网络名，None 表示默认网络
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscoverTokensResponse {
+    pub wallet: String,
+    pub ens_name: Option<String>,
+    pub tokens: Vec<String>, // 该钱包收发过的 ERC20 合约，包含之前扫描的结果
+    pub from_block: u64,     // 本次扫描的区块范围
+    pub to_block: u64,
+    pub requests: usize, // 本次发出的 eth_getLogs 请求数
+}
+
+/// 8. 转账
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TransferRequest {
+    pub to: String,                  // 收款地址或 ENS 名称
+    pub token: String,               // 地址、符号或名称，"ETH"（Polygon 上为 "POL"）表示原生代币
+    pub amount: f64,                 // 按代币精度换算前的数量，如 1.5
+    pub dry_run: Option<bool>,       // true 表示只检查余额和估算 gas，不广播交易
+    pub fee_speed: Option<FeeSpeed>, // 手续费档位，默认 normal
+    pub chain: Option<String>,       // 链 ID 或网络名，None 表示默认网络
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferResponse {
+    pub from: String,
+    pub to: String,
+    pub ens_name: Option<String>, // 收款方的 ENS 名称
+    pub amount: MetaData,
+    pub gas: GasReport,
+    pub execution: Option<TransferExecution>, // None 表示仅模拟
+}
+
+/// 已广播转账的回执
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferExecution {
+    pub tx_hash: String,
+    pub status: bool, // false 表示交易已上链但执行失败
+    pub block_number: Option<u64>,
+    pub gas_used: u64,
+}
+
+/// 9. 交易查询
+#[derive(Debug, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct GetTransactionRequest {
+    pub tx_hash: String,
+    pub chain: Option<String>, // 链 ID 或网络名，None 表示默认网络
+}
+
+/// 交易状态：pending 表示还没有回执
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Mined,
+    Failed, // 已上链但执行 revert
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTransactionResponse {
+    pub tx_hash: String,
+    pub status: TransactionStatus,
+    pub from: String,
+    pub to: Option<String>, // None 表示合约创建
+    pub value: MetaData,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub block_number: Option<u64>,
+    pub confirmations: Option<u64>, // 包含交易所在区块
+    pub gas_used: Option<u64>,
+    pub effective_gas_price: Option<String>, // Gwei
+    pub fee: Option<MetaData>,               // gas_used × effective_gas_price，以原生代币计
+    pub events: Vec<TransactionEvent>,       // 从回执日志解析出的 Transfer 和 Swap
+    pub revert_reason: Option<String>,       // 仅失败的交易
+}
+
+/// 回执日志中识别出的事件
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TransactionEvent {
+    Transfer {
+        token: String,
+        from: String,
+        to: String,
+        amount: MetaData,
+    },
+    Swap {
+        protocol: SwapProtocol, // 只会是 V2 或 V3
+        pool: String,
+        sender: String,
+        recipient: String,
+        amount_in: MetaData,  // 流入池子的数量
+        amount_out: MetaData, // 流出池子的数量
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetaData {
     pub value: U256,
     pub decimals: u8,
@@ -58,17 +352,102 @@ impl Display for MetaData {
     }
 }
 
+impl Display for PriceSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceSource::Uniswap => write!(f, "uniswap"),
+            PriceSource::Chainlink => write!(f, "chainlink"),
+            PriceSource::Auto => write!(f, "auto"),
+            PriceSource::Spot => write!(f, "spot"),
+            PriceSource::Twap => write!(f, "twap"),
+        }
+    }
+}
+
+impl Display for SwapProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapProtocol::V2 => write!(f, "v2"),
+            SwapProtocol::V3 => write!(f, "v3"),
+            SwapProtocol::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl Display for FeeSpeed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeSpeed::Slow => write!(f, "slow"),
+            FeeSpeed::Normal => write!(f, "normal"),
+            FeeSpeed::Fast => write!(f, "fast"),
+        }
+    }
+}
+
+impl Display for GasReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gas: {}{} (limit {}, {}) | max_fee: {} Gwei | priority_fee: {} Gwei | cost: {}",
+            if self.approximate { "~" } else { "" },
+            self.gas_estimate,
+            self.gas_limit,
+            self.speed,
+            self.max_fee_gwei,
+            self.priority_fee_gwei,
+            self.cost
+        )?;
+        if let Some(cost_usd) = &self.cost_usd {
+            write!(f, " (~{} USD)", format_usd(cost_usd))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SwapSide {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapSide::ExactIn => write!(f, "exact_in"),
+            SwapSide::ExactOut => write!(f, "exact_out"),
+        }
+    }
+}
+
 // 1. 余额
 impl Display for GetBalanceResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.price)
+        write!(
+            f,
+            "{} | wallet: {}",
+            self.price,
+            format_wallet(&self.wallet, &self.ens_name)
+        )?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " | block: {}", block_number)?;
+        }
+        Ok(())
     }
 }
 
 // 2. 价格（统一 6 位小数）
 impl Display for GetTokenPriceResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.price)
+        write!(f, "{} | source: {}", self.price, self.source)?;
+        match self.fees.as_slice() {
+            [] => {}
+            [fee] => write!(f, " | fee: {}", fee)?,
+            _ => write!(f, " | route: {}", format_route(&self.path, &self.fees))?,
+        }
+        if let Some(updated_at) = self.updated_at {
+            write!(f, " | updated_at: {}", updated_at)?;
+        }
+        if let Some(round_id) = &self.round_id {
+            write!(f, " | round_id: {}", round_id)?;
+        }
+        if let Some(window_secs) = self.window_secs {
+            write!(f, " | window: {}s", window_secs)?;
+        }
+        Ok(())
     }
 }
 
@@ -77,8 +456,300 @@ impl Display for SwapTokensResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "estimated_out: {} | gas_price: {} Gwei",
-            self.estimated_out, self.gas_price,
+            "protocol: {} | side: {} | route: {} | estimated_out: {} | {}",
+            self.protocol,
+            self.side,
+            format_route(&self.path, &self.fees),
+            self.estimated_out,
+            self.gas,
+        )?;
+        if let Some(approval_gas) = &self.approval_gas {
+            write!(
+                f,
+                " | approve {} | total_cost: {}",
+                approval_gas, self.total_cost
+            )?;
+            if let Some(total_cost_usd) = &self.total_cost_usd {
+                write!(f, " (~{} USD)", format_usd(total_cost_usd))?;
+            }
+        }
+        if let Some(expected_in) = &self.expected_in {
+            write!(f, " | expected_in: {}", expected_in)?;
+        }
+        if let Some(max_in) = &self.max_in {
+            write!(f, " | max_in: {}", max_in)?;
+        }
+        if let Some(execution) = &self.execution {
+            write!(f, " | {}", execution)?;
+        }
+        Ok(())
+    }
+}
+
+/// 将路径格式化为 `A -(fee)-> B -(fee)-> C`
+fn format_route(path: &[String], fees: &[u32]) -> String {
+    let mut route = String::new();
+    for (i, token) in path.iter().enumerate() {
+        route.push_str(token);
+        if let Some(fee) = fees.get(i) {
+            route.push_str(&format!(" -({})-> ", fee));
+        }
+    }
+    route
+}
+
+impl Display for SwapExecution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx_hash: {} | block_number: {} | amount_in: {} | amount_out: {} | gas_used: {} | gas_price: {} Gwei | fee: {}",
+            self.tx_hash,
+            self.block_number
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "pending".to_string()),
+            self.amount_in,
+            self.amount_out,
+            self.gas_used,
+            self.gas_price,
+            self.fee,
+        )?;
+        if let Some(approval_tx_hash) = &self.approval_tx_hash {
+            write!(f, " | approval_tx: {}", approval_tx_hash)?;
+        }
+        Ok(())
+    }
+}
+
+// 4. 价格共识
+impl Display for SourcePrice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.price, &self.error) {
+            (Some(price), _) => write!(f, "{}: {}", self.source, price),
+            (None, Some(error)) => write!(f, "{}: unavailable ({})", self.source, error),
+            (None, None) => write!(f, "{}: unavailable", self.source),
+        }
+    }
+}
+
+impl Display for GetPriceConsensusResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "token: {}", self.token)?;
+        for price in &self.prices {
+            write!(f, " | {}", price)?;
+        }
+        write!(
+            f,
+            " | median: {} | max_deviation: {} bps | threshold: {} bps | status: {}",
+            self.median,
+            self.max_deviation_bps,
+            self.threshold_bps,
+            if self.flagged { "flagged" } else { "ok" },
         )
     }
 }
+
+// 5. 批量余额（表格形式，失败的单元格显示错误）
+impl Display for GetBalancesResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(block_number) = self.block_number {
+            writeln!(f, "block: {}", block_number)?;
+        }
+        write!(f, "wallet | {}", self.columns.join(" | "))?;
+        for row in &self.rows {
+            write!(f, "\n{}", format_wallet(&row.wallet, &row.ens_name))?;
+            for cell in &row.balances {
+                match (&cell.balance, &cell.error) {
+                    (Some(balance), _) => write!(
+                        f,
+                        " | {}",
+                        format_units(balance.value, balance.decimals)
+                            .unwrap_or_else(|_| "0".to_string())
+                    )?,
+                    (None, Some(error)) => write!(f, " | error ({})", error)?,
+                    (None, None) => write!(f, " | -")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 6. 持仓估值（USD 金额保留 2 位小数）
+impl Display for PortfolioHolding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.amount)?;
+        match (&self.value_usd, &self.error) {
+            (Some(value), _) => write!(
+                f,
+                " | {} USD | {:.2}%",
+                format_usd(value),
+                f64::from(self.share_bps.unwrap_or(0)) / 100.0
+            ),
+            (None, Some(error)) => write!(f, " | unpriced ({})", error),
+            (None, None) => write!(f, " | unpriced"),
+        }
+    }
+}
+
+impl Display for GetPortfolioResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wallet: {} | total: {} USD",
+            format_wallet(&self.wallet, &self.ens_name),
+            format_usd(&self.total_value_usd)
+        )?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " | block: {}", block_number)?;
+        }
+        if self.dust_skipped > 0 {
+            write!(f, " | dust skipped: {}", self.dust_skipped)?;
+        }
+        for holding in &self.holdings {
+            write!(f, "\n{}", holding)?;
+        }
+        Ok(())
+    }
+}
+
+/// 有 ENS 名称时显示为 `name (0x...)`，便于核对解析结果
+fn format_wallet(wallet: &str, ens_name: &Option<String>) -> String {
+    match ens_name {
+        Some(name) => format!("{} ({})", name, wallet),
+        None => wallet.to_string(),
+    }
+}
+
+fn format_usd(value: &MetaData) -> String {
+    let value: f64 = format_units(value.value, value.decimals)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0.0);
+    format!("{:.2}", value)
+}
+
+// 7. 代币发现
+impl Display for DiscoverTokensResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wallet: {} | scanned: {}..={} | requests: {} | tokens: {}",
+            format_wallet(&self.wallet, &self.ens_name),
+            self.from_block,
+            self.to_block,
+            self.requests,
+            self.tokens.len()
+        )?;
+        for token in &self.tokens {
+            write!(f, "\n{}", token)?;
+        }
+        Ok(())
+    }
+}
+
+// 8. 转账
+impl Display for TransferResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "transfer {} | from: {} | to: {} | {}",
+            self.amount,
+            self.from,
+            format_wallet(&self.to, &self.ens_name),
+            self.gas
+        )?;
+        match &self.execution {
+            Some(execution) => write!(f, " | {}", execution),
+            None => write!(f, " | dry run, not broadcast"),
+        }
+    }
+}
+
+impl Display for TransferExecution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx: {} | status: {}",
+            self.tx_hash,
+            if self.status { "success" } else { "reverted" }
+        )?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " | block: {}", block_number)?;
+        }
+        write!(f, " | gas_used: {}", self.gas_used)
+    }
+}
+
+// 9. 交易查询
+impl Display for TransactionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionStatus::Pending => write!(f, "pending"),
+            TransactionStatus::Mined => write!(f, "mined"),
+            TransactionStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl Display for GetTransactionResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx: {} | status: {} | from: {} | to: {} | value: {} | nonce: {}",
+            self.tx_hash,
+            self.status,
+            self.from,
+            self.to.as_deref().unwrap_or("contract creation"),
+            self.value,
+            self.nonce
+        )?;
+        if let Some(block_number) = self.block_number {
+            write!(f, " | block: {}", block_number)?;
+        }
+        if let Some(confirmations) = self.confirmations {
+            write!(f, " | confirmations: {}", confirmations)?;
+        }
+        match self.gas_used {
+            Some(gas_used) => write!(f, " | gas_used: {} / {}", gas_used, self.gas_limit)?,
+            None => write!(f, " | gas_limit: {}", self.gas_limit)?,
+        }
+        if let Some(price) = &self.effective_gas_price {
+            write!(f, " | gas_price: {} Gwei", price)?;
+        }
+        if let Some(fee) = &self.fee {
+            write!(f, " | fee: {}", fee)?;
+        }
+        if let Some(reason) = &self.revert_reason {
+            write!(f, " | revert_reason: {}", reason)?;
+        }
+        for event in &self.events {
+            write!(f, "\n{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TransactionEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionEvent::Transfer {
+                token,
+                from,
+                to,
+                amount,
+            } => write!(f, "transfer {} ({}) | {} -> {}", amount, token, from, to),
+            TransactionEvent::Swap {
+                protocol,
+                pool,
+                sender,
+                recipient,
+                amount_in,
+                amount_out,
+            } => write!(
+                f,
+                "swap {} pool {} | {} -> {} | sender: {} | recipient: {}",
+                protocol, pool, amount_in, amount_out, sender, recipient
+            ),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use crate::models::{GetBalanceRequest, GetTokenPriceRequest, SwapTokensRequest};
+use crate::config::Config;
+use crate::models::{
+    DiscoverTokensRequest, GetBalanceRequest, GetBalancesRequest, GetPortfolioRequest,
+    GetPriceConsensusRequest, GetTokenPriceRequest, GetTransactionRequest, SwapTokensRequest,
+    TransferRequest,
+};
 use crate::services::WalletService;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::{
@@ -15,13 +20,21 @@ pub struct McpServer {
 #[tool_router]
 impl McpServer {
     #[allow(dead_code)]
-    pub fn new(private_key: String, rpc_url: String) -> Self {
-        Self {
+    pub async fn new(private_key: String, rpc_url: String) -> anyhow::Result<Self> {
+        Ok(Self {
             tool_router: Self::tool_router(),
-            wallet_service: WalletService::new(private_key, rpc_url),
-        }
+            wallet_service: WalletService::new(private_key, rpc_url).await?,
+        })
+    }
+
+    /// 使用配置文件中的所有网络、默认账户和交易策略创建服务
+    pub async fn from_config(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self {
+            tool_router: Self::tool_router(),
+            wallet_service: WalletService::from_config(config).await?,
+        })
     }
-    
+
     #[tool(description = "Query ETH and ERC20 token balances")]
     pub async fn get_balance(
         &self,
@@ -30,7 +43,37 @@ impl McpServer {
         self.wallet_service.get_balance(request).await
     }
 
-    #[tool(description = "Get current token price in USD or ETH")]
+    #[tool(
+        description = "Query native and ERC20 balances for many wallets and tokens in one Multicall3 call"
+    )]
+    pub async fn get_balances(
+        &self,
+        request: Parameters<GetBalancesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.get_balances(request).await
+    }
+
+    #[tool(
+        description = "Discover ERC20 tokens a wallet has sent or received by scanning Transfer logs"
+    )]
+    pub async fn discover_tokens(
+        &self,
+        request: Parameters<DiscoverTokensRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.discover_tokens(request).await
+    }
+
+    #[tool(
+        description = "Value a wallet's holdings in USD with per-token amount, value and portfolio share"
+    )]
+    pub async fn get_portfolio(
+        &self,
+        request: Parameters<GetPortfolioRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.get_portfolio(request).await
+    }
+
+    #[tool(description = "Get current token price in USD, ETH or any quote token")]
     pub async fn get_token_price(
         &self,
         request: Parameters<GetTokenPriceRequest>,
@@ -38,6 +81,16 @@ impl McpServer {
         self.wallet_service.get_token_price(request).await
     }
 
+    #[tool(
+        description = "Compare Uniswap quote, Chainlink feed and pool TWAP prices and flag large deviations"
+    )]
+    pub async fn get_price_consensus(
+        &self,
+        request: Parameters<GetPriceConsensusRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.get_price_consensus(request).await
+    }
+
     #[tool(description = "Execute a token swap on Uniswap V2 or V3")]
     pub async fn swap_tokens(
         &self,
@@ -45,6 +98,26 @@ impl McpServer {
     ) -> Result<CallToolResult, McpError> {
         self.wallet_service.swap_tokens(request).await
     }
+
+    #[tool(
+        description = "Send native ETH or an ERC20 token to an address or ENS name after checking balance and estimating gas"
+    )]
+    pub async fn transfer(
+        &self,
+        request: Parameters<TransferRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.transfer(request).await
+    }
+
+    #[tool(
+        description = "Look up a transaction by hash: status, confirmations, gas, decoded Transfer/Swap events and revert reason"
+    )]
+    pub async fn get_transaction(
+        &self,
+        request: Parameters<GetTransactionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.wallet_service.get_transaction(request).await
+    }
 }
 
 #[tool_handler]
@@ -55,7 +128,7 @@ impl ServerHandler for McpServer {
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(
-                "This server provides wallet tools. Tools: get_balance get_token_price swap_tokens.".to_string(),
+                "This server provides wallet tools. Tools: get_balance get_balances discover_tokens get_portfolio get_token_price get_price_consensus swap_tokens transfer get_transaction. Every tool accepts an optional chain (chain id or network name).".to_string(),
             ),
         }
     }
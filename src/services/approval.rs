@@ -0,0 +1,89 @@
+use crate::models::IERC20;
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::state::{StateOverride, StateOverridesBuilder};
+use futures::future::join_all;
+
+/// 探测 allowance mapping 所在的存储槽位范围，常见代币（USDC 为 10、USDT 为 5）都在其中
+const MAX_ALLOWANCE_SLOT: u64 = 32;
+
+/// Solidity 布局：allowance[owner][spender] 位于 keccak(spender . keccak(owner . slot))
+fn solidity_slot(owner: Address, spender: Address, slot: u64) -> B256 {
+    let inner = keccak256([owner.into_word().0, U256::from(slot).to_be_bytes::<32>()].concat());
+    keccak256([spender.into_word().0, inner.0].concat())
+}
+
+/// Vyper 布局：键和槽位的顺序相反
+fn vyper_slot(owner: Address, spender: Address, slot: u64) -> B256 {
+    let inner = keccak256([U256::from(slot).to_be_bytes::<32>(), owner.into_word().0].concat());
+    keccak256([inner.0, spender.into_word().0].concat())
+}
+
+/// 构造把 owner 对 spender 的授权额度覆盖为最大值的 state override，用于 dry_run 时在授权前模拟交换和估算 gas
+///
+/// 代币的存储布局未知，同时尝试各个候选槽位，以覆盖后 allowance() 返回最大值为准；打包存储的授权额度（如 UNI 的 uint96）、
+/// ERC-7201 命名空间存储或节点不支持 state override 时找不到槽位，返回 None
+pub async fn allowance_override<P: Provider + Clone>(
+    provider: P,
+    token: Address,
+    owner: Address,
+    spender: Address,
+) -> Option<StateOverride> {
+    let candidates: Vec<B256> = (0..MAX_ALLOWANCE_SLOT)
+        .flat_map(|slot| {
+            [
+                solidity_slot(owner, spender, slot),
+                vyper_slot(owner, spender, slot),
+            ]
+        })
+        .collect();
+    let erc20 = IERC20::new(token, provider);
+    let probes = candidates.iter().map(|&slot| {
+        let overrides = override_slot(token, slot);
+        let call = erc20.allowance(owner, spender).state(overrides);
+        async move { call.call().await }
+    });
+    let results = join_all(probes).await;
+    candidates
+        .iter()
+        .zip(results)
+        .find(|(_, result)| matches!(result, Ok(allowance) if *allowance == U256::MAX))
+        .map(|(&slot, _)| override_slot(token, slot))
+}
+
+fn override_slot(token: Address, slot: B256) -> StateOverride {
+    StateOverridesBuilder::default()
+        .with_state_diff(token, [(slot, B256::from(U256::MAX))])
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, b256};
+
+    #[test]
+    fn test_allowance_slots() {
+        let owner = address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        let spender = address!("0xE592427A0AEce92De3Edee1F18E0157C05861564");
+        let slot = solidity_slot(owner, spender, 10);
+        // keccak256(abi.encode(spender, keccak256(abi.encode(owner, 10))))
+        let inner = keccak256(
+            [
+                b256!("0x000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb92266").0,
+                U256::from(10).to_be_bytes::<32>(),
+            ]
+            .concat(),
+        );
+        let expected = keccak256(
+            [
+                b256!("0x000000000000000000000000e592427a0aece92de3edee1f18e0157c05861564").0,
+                inner.0,
+            ]
+            .concat(),
+        );
+        assert_eq!(slot, expected);
+        assert_ne!(slot, vyper_slot(owner, spender, 10));
+        assert_ne!(slot, solidity_slot(spender, owner, 10));
+    }
+}
@@ -0,0 +1,192 @@
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::{DynProvider, Provider};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use rmcp::ErrorData as McpError;
+use std::str::FromStr;
+
+/// 历史查询的目标区块：区块号、标签或时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSpec {
+    Number(u64),
+    Tag(BlockNumberOrTag),
+    Timestamp(u64), // Unix 秒，取该时刻或之前的最后一个区块
+}
+
+/// 解析 block 参数：十进制或 0x 区块号、latest/safe/finalized 等标签、ISO 8601 时间戳
+///
+/// 没有时区的时间和纯日期（如 `2024-01-31`）按 UTC 处理
+pub fn parse_block_spec(input: &str) -> Result<BlockSpec, McpError> {
+    let input = input.trim();
+    if let Ok(number) = input.parse::<u64>() {
+        return Ok(BlockSpec::Number(number));
+    }
+    if let Ok(tag) = BlockNumberOrTag::from_str(input) {
+        return Ok(match tag {
+            BlockNumberOrTag::Number(number) => BlockSpec::Number(number),
+            tag => BlockSpec::Tag(tag),
+        });
+    }
+    let timestamp = DateTime::parse_from_rfc3339(input)
+        .map(|time| time.timestamp())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S")
+                .map(|time| time.and_utc().timestamp())
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .map(|date| date.and_time(Default::default()).and_utc().timestamp())
+        })
+        .map_err(|_| {
+            McpError::invalid_params(
+                format!(
+                    "Invalid block '{}': expected a block number, a tag (latest, safe, finalized, earliest) or an ISO 8601 timestamp",
+                    input
+                ),
+                None,
+            )
+        })?;
+    let timestamp = u64::try_from(timestamp).map_err(|_| {
+        McpError::invalid_params(format!("Timestamp '{}' is before 1970", input), None)
+    })?;
+    Ok(BlockSpec::Timestamp(timestamp))
+}
+
+/// 把 block 参数解析为具体区块号，时间戳通过二分查找区块头换算
+pub async fn resolve_block(provider: &DynProvider, block: &str) -> Result<u64, McpError> {
+    match parse_block_spec(block)? {
+        BlockSpec::Number(number) => Ok(number),
+        BlockSpec::Tag(tag) => Ok(block_header(provider, tag).await?.0),
+        BlockSpec::Timestamp(timestamp) => block_at_timestamp(provider, timestamp).await,
+    }
+}
+
+/// 时间戳不晚于 timestamp 的最后一个区块
+pub async fn block_at_timestamp(provider: &DynProvider, timestamp: u64) -> Result<u64, McpError> {
+    let (latest, latest_timestamp) = block_header(provider, BlockNumberOrTag::Latest).await?;
+    if timestamp >= latest_timestamp {
+        if timestamp > latest_timestamp {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Timestamp {} is after the latest block {} ({})",
+                    timestamp, latest, latest_timestamp
+                ),
+                None,
+            ));
+        }
+        return Ok(latest);
+    }
+    let (_, genesis_timestamp) = block_header(provider, BlockNumberOrTag::Number(0)).await?;
+    if timestamp < genesis_timestamp {
+        return Err(McpError::invalid_params(
+            format!(
+                "Timestamp {} is before the genesis block ({})",
+                timestamp, genesis_timestamp
+            ),
+            None,
+        ));
+    }
+
+    // 不变式：low 的时间戳 <= timestamp < high 的时间戳
+    let (mut low, mut high) = (0, latest);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let (_, mid_timestamp) = block_header(provider, BlockNumberOrTag::Number(mid)).await?;
+        if mid_timestamp <= timestamp {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// 返回区块号和时间戳
+async fn block_header(
+    provider: &DynProvider,
+    block: BlockNumberOrTag,
+) -> Result<(u64, u64), McpError> {
+    let header = provider
+        .get_block_by_number(block)
+        .await
+        .map_err(|e| {
+            McpError::internal_error(format!("Failed to get block {}: {}", block, e), None)
+        })?
+        .ok_or_else(|| McpError::invalid_params(format!("Block {} not found", block), None))?
+        .header;
+    Ok((header.number, header.timestamp))
+}
+
+/// 非归档节点查询已裁剪的历史状态时各客户端返回的错误
+pub fn is_missing_state_error(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "missing trie node",
+        "state is not available",
+        "state not available",
+        "historical state",
+        "pruned",
+        "old data not available",
+        "distance to target block exceeds",
+        "state histories haven't been fully indexed",
+        "not an archive node",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// 历史区块查询失败时，缺少状态的错误提示需要归档节点
+pub fn historical_error(context: &str, block: u64, error: impl std::fmt::Display) -> McpError {
+    let message = error.to_string();
+    if is_missing_state_error(&message) {
+        return McpError::invalid_params(
+            format!(
+                "{} at block {}: the node has pruned this state, historical queries require an archive node ({})",
+                context, block, message
+            ),
+            None,
+        );
+    }
+    McpError::internal_error(format!("{} at block {}: {}", context, block, message), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_spec() {
+        assert_eq!(
+            parse_block_spec("19000000").unwrap(),
+            BlockSpec::Number(19_000_000)
+        );
+        assert_eq!(parse_block_spec("0x10").unwrap(), BlockSpec::Number(16));
+        assert_eq!(
+            parse_block_spec("finalized").unwrap(),
+            BlockSpec::Tag(BlockNumberOrTag::Finalized)
+        );
+        assert_eq!(
+            parse_block_spec("2024-01-31T23:59:59Z").unwrap(),
+            BlockSpec::Timestamp(1_706_745_599)
+        );
+        assert_eq!(
+            parse_block_spec("2024-02-01T07:59:59+08:00").unwrap(),
+            BlockSpec::Timestamp(1_706_745_599)
+        );
+        assert_eq!(
+            parse_block_spec("2024-02-01").unwrap(),
+            BlockSpec::Timestamp(1_706_745_600)
+        );
+        assert!(parse_block_spec("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_missing_state_errors() {
+        assert!(is_missing_state_error(
+            "server returned an error response: error code -32000: missing trie node 1a2b (path )"
+        ));
+        assert!(is_missing_state_error(
+            "old data not available due to pruning"
+        ));
+        assert!(!is_missing_state_error("execution reverted"));
+    }
+}
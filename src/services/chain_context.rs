@@ -0,0 +1,407 @@
+use crate::config::{ContractsConfig, NetworkConfig, RpcConfig};
+use crate::models::{chain_by_id, chain_by_name, ChainAddresses, MULTICALL3};
+use crate::services::ens::{self, EnsResolver, ResolvedAddress};
+use crate::services::rpc;
+use crate::services::token_registry::TokenRegistry;
+use alloy::primitives::Address;
+use alloy::providers::DynProvider;
+use anyhow::{anyhow, Result};
+use futures::future::try_join_all;
+use rmcp::ErrorData as McpError;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// 一条已配置的链：网络名、RPC、合约地址表和启动时建立的共享只读 provider
+pub struct ChainContext {
+    pub name: String,          // 配置中的网络名
+    pub rpc_urls: Vec<String>, // 主节点在前，之后是备用节点
+    pub addresses: ChainAddresses,
+    pub provider: DynProvider, // 带超时、故障转移和限流重试，所有请求共用
+    pub tokens: Arc<TokenRegistry>, // 配置的 token list，所有链共用
+    pub ens: Option<EnsResolver>, // 本链没有 ENS 时使用主网的
+}
+
+impl ChainContext {
+    pub async fn connect(
+        name: String,
+        rpc_urls: Vec<String>,
+        quorum: Option<usize>,
+        addresses: ChainAddresses,
+        rpc: &RpcConfig,
+    ) -> Result<Self> {
+        let provider = rpc::connect(&rpc_urls, quorum, rpc)
+            .await
+            .map_err(|e| anyhow!("Network '{}': {:#}", name, e))?;
+        let ens = addresses
+            .ens_registry
+            .map(|registry| EnsResolver::new(provider.clone(), registry));
+        Ok(Self {
+            name,
+            rpc_urls,
+            addresses,
+            provider,
+            tokens: Arc::default(),
+            ens,
+        })
+    }
+
+    /// 设置按符号解析代币时使用的 token list
+    pub fn with_tokens(mut self, tokens: Arc<TokenRegistry>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    pub async fn from_network(
+        name: &str,
+        network: &NetworkConfig,
+        rpc: &RpcConfig,
+    ) -> Result<Self> {
+        let addresses = network_addresses(name, network)?;
+        Self::connect(
+            name.to_string(),
+            network.rpc_urls(),
+            network.quorum,
+            addresses,
+            rpc,
+        )
+        .await
+    }
+
+    /// 主 RPC 地址
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_urls[0]
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.addresses.chain_id
+    }
+
+    /// 设置解析 ENS 名称使用的 resolver
+    pub fn with_ens(mut self, ens: EnsResolver) -> Self {
+        self.ens = Some(ens);
+        self
+    }
+
+    /// 解析地址或 ENS 名称；输入为地址时尽量反向解析出主名称
+    pub async fn resolve_address(
+        &self,
+        input: &str,
+        kind: &str,
+    ) -> Result<ResolvedAddress, McpError> {
+        let input = input.trim();
+        if let Ok(address) = Address::from_str(input) {
+            return Ok(ResolvedAddress {
+                address,
+                ens_name: self.lookup_name(address).await,
+            });
+        }
+        if !ens::is_ens_name(input) {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Invalid {} address '{}': expected a hex address or an ENS name",
+                    kind, input
+                ),
+                None,
+            ));
+        }
+        let Some(resolver) = &self.ens else {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Cannot resolve ENS name '{}': no ENS registry on {} and mainnet is not configured",
+                    input, self.name
+                ),
+                None,
+            ));
+        };
+        let name = ens::normalize(input)?;
+        let address = resolver.resolve(&name).await?;
+        Ok(ResolvedAddress {
+            address,
+            ens_name: Some(name),
+        })
+    }
+
+    pub async fn resolve_addresses(
+        &self,
+        inputs: &[String],
+        kind: &str,
+    ) -> Result<Vec<ResolvedAddress>, McpError> {
+        try_join_all(inputs.iter().map(|input| self.resolve_address(input, kind))).await
+    }
+
+    /// 反向解析地址的 ENS 主名称，没有 ENS 或查询失败时返回 None
+    pub async fn lookup_name(&self, address: Address) -> Option<String> {
+        let resolver = self.ens.as_ref()?;
+        match resolver.lookup(address).await {
+            Ok(name) => name,
+            Err(error) => {
+                tracing::debug!(
+                    "ENS reverse lookup of {} failed: {}",
+                    address,
+                    error.message
+                );
+                None
+            }
+        }
+    }
+
+    /// 把地址、符号或名称解析为该链上的代币地址
+    ///
+    /// 能解析为地址时直接使用；否则按符号或名称查找，内置地址表（含 contracts 覆盖）优先，其次查 token list
+    ///
+    /// 以 0x 开头的符号（如 0xBTC）不是合法地址，同样按符号查找
+    pub fn resolve_token(&self, token: &str) -> Result<Address, McpError> {
+        let token = token.trim();
+        let address_error = match Address::from_str(token) {
+            Ok(address) => return Ok(address),
+            Err(e) => e,
+        };
+        if let Some((_, address)) = self
+            .addresses
+            .known_tokens()
+            .into_iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(token))
+        {
+            return Ok(address);
+        }
+        match self.tokens.resolve(self.chain_id(), token)? {
+            Some(info) => Ok(info.address),
+            None if token.starts_with("0x") => Err(McpError::invalid_params(
+                format!(
+                    "Invalid token address '{}' and no token with that symbol on {}: {}",
+                    token, self.name, address_error
+                ),
+                None,
+            )),
+            None => Err(McpError::invalid_params(
+                format!(
+                    "Unknown token '{}' on {}, use an address or add it to a token list",
+                    token, self.name
+                ),
+                None,
+            )),
+        }
+    }
+
+    /// 代币是否在内置地址表（含 contracts 覆盖）或 token list 中
+    pub fn is_known_token(&self, token: Address) -> bool {
+        self.addresses
+            .known_tokens()
+            .iter()
+            .any(|(_, address)| *address == token)
+            || self.tokens.contains(self.chain_id(), token)
+    }
+
+    pub fn resolve_tokens(&self, tokens: &[String]) -> Result<Vec<Address>, McpError> {
+        tokens
+            .iter()
+            .map(|token| self.resolve_token(token))
+            .collect()
+    }
+
+    /// V3 SwapRouter 地址，该链未部署时返回错误
+    pub fn swap_router(&self) -> Result<Address, McpError> {
+        self.addresses.swap_router.ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Uniswap V3 SwapRouter is not available on {}, configure contracts.swap_router",
+                    self.name
+                ),
+                None,
+            )
+        })
+    }
+
+    /// V2 Router02 地址，该链未部署时返回错误
+    pub fn v2_router(&self) -> Result<Address, McpError> {
+        self.addresses.v2_router.ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "Uniswap V2 router is not available on {}, configure contracts.v2_router",
+                    self.name
+                ),
+                None,
+            )
+        })
+    }
+}
+
+/// 链 ID 或网络名命中内置地址表时以其为基础，再应用 contracts 覆盖
+pub fn network_addresses(name: &str, network: &NetworkConfig) -> Result<ChainAddresses> {
+    let builtin = match network.chain_id {
+        Some(chain_id) => chain_by_id(chain_id),
+        None => chain_by_name(name),
+    };
+    match builtin {
+        Some(builtin) => Ok(apply_contracts(builtin.clone(), &network.contracts)),
+        None => custom_chain(name, network),
+    }
+}
+
+fn apply_contracts(mut addresses: ChainAddresses, contracts: &ContractsConfig) -> ChainAddresses {
+    if let Some(weth) = contracts.weth {
+        // 只有包装代币就是 WETH 的链上才同时覆盖 wrapped_native
+        if addresses.wrapped_native == addresses.weth {
+            addresses.wrapped_native = weth;
+        }
+        addresses.weth = weth;
+    }
+    addresses.usdc = contracts.usdc.unwrap_or(addresses.usdc);
+    addresses.usdt = contracts.usdt.or(addresses.usdt);
+    addresses.dai = contracts.dai.or(addresses.dai);
+    addresses.wbtc = contracts.wbtc.or(addresses.wbtc);
+    addresses.quoter_v2 = contracts.quoter_v2.unwrap_or(addresses.quoter_v2);
+    addresses.multicall3 = contracts.multicall3.unwrap_or(addresses.multicall3);
+    addresses.v3_factory = contracts.v3_factory.unwrap_or(addresses.v3_factory);
+    addresses.swap_router = contracts.swap_router.or(addresses.swap_router);
+    addresses.v2_router = contracts.v2_router.or(addresses.v2_router);
+    addresses.feed_registry = contracts.feed_registry.or(addresses.feed_registry);
+    addresses.ens_registry = contracts.ens_registry.or(addresses.ens_registry);
+    addresses
+}
+
+/// 不在内置地址表中的链，必须配置链 ID 和 weth/usdc/quoter_v2/v3_factory，链名取网络名，原生代币符号取 native_symbol
+fn custom_chain(name: &str, network: &NetworkConfig) -> Result<ChainAddresses> {
+    let contracts = &network.contracts;
+    let required = |address: Option<_>, field: &str| {
+        address.ok_or_else(|| {
+            anyhow!(
+                "Network '{}' is not a built-in chain, contracts.{} is required",
+                name,
+                field
+            )
+        })
+    };
+    let weth = required(contracts.weth, "weth")?;
+    Ok(ChainAddresses {
+        chain_id: network.chain_id.ok_or_else(|| {
+            anyhow!(
+                "Network '{}' is not a built-in chain, chain_id is required",
+                name
+            )
+        })?,
+        name: leak(name),
+        native_symbol: leak(network.native_symbol.as_deref().unwrap_or("ETH")),
+        wrapped_native: weth,
+        weth,
+        usdc: required(contracts.usdc, "usdc")?,
+        usdt: contracts.usdt,
+        dai: contracts.dai,
+        wbtc: contracts.wbtc,
+        v3_factory: required(contracts.v3_factory, "v3_factory")?,
+        quoter_v2: required(contracts.quoter_v2, "quoter_v2")?,
+        multicall3: contracts.multicall3.unwrap_or(MULTICALL3),
+        swap_router: contracts.swap_router,
+        v2_router: contracts.v2_router,
+        feed_registry: contracts.feed_registry,
+        ens_registry: contracts.ens_registry,
+        usd_feeds: &[],
+    })
+}
+
+/// 地址表中的名称是 'static，配置的网络只在启动时各构造一次，泄漏的字符串数量有限
+fn leak(value: &str) -> &'static str {
+    Box::leak(value.to_string().into_boxed_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ARBITRUM, MAINNET};
+    use alloy::primitives::address;
+
+    fn network(chain_id: Option<u64>) -> NetworkConfig {
+        NetworkConfig {
+            rpc_url: "http://localhost:8545".to_string(),
+            chain_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_chain_by_id_and_name() {
+        let addresses = network_addresses("arb", &network(Some(42161))).unwrap();
+        assert_eq!(addresses, ARBITRUM);
+        let addresses = network_addresses("mainnet", &network(None)).unwrap();
+        assert_eq!(addresses, MAINNET);
+    }
+
+    #[test]
+    fn test_contract_overrides() {
+        let usdc = address!("0x0000000000000000000000000000000000000001");
+        let mut config = network(Some(1));
+        config.contracts.usdc = Some(usdc);
+        let addresses = network_addresses("mainnet", &config).unwrap();
+        assert_eq!(addresses.usdc, usdc);
+        assert_eq!(addresses.weth, MAINNET.weth);
+    }
+
+    #[test]
+    fn test_custom_chain_requires_contracts() {
+        assert!(network_addresses("devnet", &network(Some(31337))).is_err());
+    }
+
+    #[test]
+    fn test_custom_chain_name_and_symbol() {
+        let mut config = network(Some(43114));
+        config.native_symbol = Some("AVAX".to_string());
+        config.contracts.weth = Some(address!("0xB31f66AA3C1e785363F0875A1B74E27b85FD66c7"));
+        config.contracts.usdc = Some(address!("0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E"));
+        config.contracts.quoter_v2 = Some(address!("0xbe0F5544EC67e9B3b2D979aaA43f18Fd87E6257F"));
+        config.contracts.v3_factory = Some(address!("0x740b1c1de25031C31FF4fC9A62f554A55cdC1baD"));
+        let addresses = network_addresses("avalanche", &config).unwrap();
+        assert_eq!(addresses.name, "avalanche");
+        assert_eq!(addresses.native_symbol, "AVAX");
+        assert_eq!(addresses.wrapped_native, config.contracts.weth.unwrap());
+
+        config.native_symbol = None;
+        assert_eq!(
+            network_addresses("avalanche", &config)
+                .unwrap()
+                .native_symbol,
+            "ETH"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_token() {
+        let mut tokens = TokenRegistry::default();
+        tokens
+            .extend_from_json(
+                r#"{"tokens": [
+                    { "chainId": 1, "address": "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984", "name": "Uniswap", "symbol": "UNI", "decimals": 18 },
+                    { "chainId": 1, "address": "0x0000000000000000000000000000000000000001", "name": "Bridged USDC", "symbol": "USDC", "decimals": 6 },
+                    { "chainId": 1, "address": "0xB6eD7644C69416d67B522e20bC294A9a9B405B31", "name": "0xBitcoin Token", "symbol": "0xBTC", "decimals": 8 }
+                ]}"#,
+            )
+            .unwrap();
+        let chain = ChainContext::connect(
+            "mainnet".to_string(),
+            vec!["http://localhost:8545".to_string()],
+            None,
+            MAINNET,
+            &RpcConfig::default(),
+        )
+        .await
+        .unwrap()
+        .with_tokens(Arc::new(tokens));
+
+        // 内置地址表优先于 token list
+        assert_eq!(chain.resolve_token("usdc").unwrap(), MAINNET.usdc);
+        assert_eq!(
+            chain.resolve_token("UNI").unwrap(),
+            address!("0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984")
+        );
+        assert_eq!(
+            chain.resolve_token(&MAINNET.weth.to_string()).unwrap(),
+            MAINNET.weth
+        );
+        // 以 0x 开头但不是地址的符号
+        assert_eq!(
+            chain.resolve_token("0xBTC").unwrap(),
+            address!("0xB6eD7644C69416d67B522e20bC294A9a9B405B31")
+        );
+        assert!(chain.resolve_token("NOPE").is_err());
+        assert!(chain.resolve_token("0x1234").is_err());
+    }
+}
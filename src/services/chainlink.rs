@@ -0,0 +1,216 @@
+use crate::models::{AggregatorV3Interface, ChainAddresses, FeedRegistryInterface};
+use crate::models::{DENOMINATION_BTC, DENOMINATION_ETH, DENOMINATION_USD, MAINNET};
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use rmcp::ErrorData as McpError;
+
+/// 默认允许的最大喂价延迟：ETH/USD、BTC/USD 等主要喂价心跳为 1 小时，再留 15 分钟余量
+pub const DEFAULT_MAX_PRICE_AGE_SECS: u64 = 75 * 60;
+
+/// 已知心跳为 24 小时的喂价允许的最大延迟，再留 1 小时余量
+pub const DAILY_FEED_MAX_AGE_SECS: u64 = 25 * 60 * 60;
+
+/// 未指定最大延迟时按喂价心跳选择：只有已知每日更新的主网 USDC/USD、USDT/USD 放宽到 25 小时
+pub fn default_max_age(chain: &ChainAddresses, token: Address) -> u64 {
+    let daily =
+        chain.chain_id == MAINNET.chain_id && (token == chain.usdc || Some(token) == chain.usdt);
+    if daily {
+        DAILY_FEED_MAX_AGE_SECS
+    } else {
+        DEFAULT_MAX_PRICE_AGE_SECS
+    }
+}
+
+/// 一次 Chainlink 喂价读数
+#[derive(Debug, Clone)]
+pub struct ChainlinkPrice {
+    pub feed: Address,
+    pub value: U256,
+    pub decimals: u8,
+    pub updated_at: u64,
+    pub round_id: String,
+}
+
+/// latestRoundData 中用于校验的字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundData {
+    pub round_id: u128,
+    pub answer_positive: bool,
+    pub updated_at: u64,
+    pub answered_in_round: u128,
+}
+
+/// 校验喂价轮次：价格为正、轮次已完成、更新时间不超过 max_age
+pub fn validate_round(round: &RoundData, now: u64, max_age: u64) -> Result<(), String> {
+    if !round.answer_positive {
+        return Err("feed returned a non-positive answer".to_string());
+    }
+    if round.updated_at == 0 {
+        return Err(format!("round {} is not complete", round.round_id));
+    }
+    if round.answered_in_round < round.round_id {
+        return Err(format!(
+            "round {} was answered in stale round {}",
+            round.round_id, round.answered_in_round
+        ));
+    }
+    let age = now.saturating_sub(round.updated_at);
+    if age > max_age {
+        return Err(format!(
+            "price is stale: updated {}s ago, max allowed {}s",
+            age, max_age
+        ));
+    }
+    Ok(())
+}
+
+/// Feed Registry 中 WETH/WBTC 分别以 ETH/BTC 计价单位登记
+fn registry_base(chain: &ChainAddresses, token: Address) -> Address {
+    if token == chain.weth {
+        DENOMINATION_ETH
+    } else if Some(token) == chain.wbtc {
+        DENOMINATION_BTC
+    } else {
+        token
+    }
+}
+
+/// 先查询 Feed Registry（仅主网部署），失败时回退到该链的内置喂价表
+pub async fn resolve_usd_feed<P: Provider>(
+    provider: P,
+    chain: &ChainAddresses,
+    token: Address,
+) -> Result<Address, McpError> {
+    if let Some(registry) = chain.feed_registry {
+        let registry = FeedRegistryInterface::new(registry, provider);
+        if let Ok(feed) = registry
+            .getFeed(registry_base(chain, token), DENOMINATION_USD)
+            .call()
+            .await
+        {
+            if feed != Address::ZERO {
+                return Ok(feed);
+            }
+        }
+    }
+    chain.usd_feed(token).ok_or_else(|| {
+        McpError::invalid_params(
+            format!(
+                "No Chainlink USD feed for token {} on {}",
+                token, chain.name
+            ),
+            None,
+        )
+    })
+}
+
+/// 读取代币的 Chainlink USD 价格，并校验轮次和新鲜度，max_age 为 None 时按喂价心跳取默认值
+pub async fn latest_usd_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token: Address,
+    max_age: Option<u64>,
+) -> Result<ChainlinkPrice, McpError> {
+    let max_age = max_age.unwrap_or_else(|| default_max_age(chain, token));
+    let feed = resolve_usd_feed(provider.clone(), chain, token).await?;
+    let aggregator = AggregatorV3Interface::new(feed, provider.clone());
+    let round = aggregator.latestRoundData().call().await.map_err(|e| {
+        McpError::internal_error(
+            format!("Failed to read Chainlink feed {}: {}", feed, e),
+            None,
+        )
+    })?;
+    let decimals = aggregator.decimals().call().await.map_err(|e| {
+        McpError::internal_error(format!("Failed to get feed decimals: {}", e), None)
+    })?;
+
+    // 以最新区块时间判断新鲜度，分叉节点上的系统时间并不可靠
+    let now = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await
+        .map_err(|e| McpError::internal_error(format!("Failed to get latest block: {}", e), None))?
+        .ok_or_else(|| McpError::internal_error("Latest block not found", None))?
+        .header
+        .timestamp;
+
+    let updated_at = u64::try_from(round.updatedAt).unwrap_or(u64::MAX);
+    let round_data = RoundData {
+        round_id: round.roundId.to::<u128>(),
+        answer_positive: round.answer.is_positive(),
+        updated_at,
+        answered_in_round: round.answeredInRound.to::<u128>(),
+    };
+    validate_round(&round_data, now, max_age).map_err(|e| {
+        McpError::internal_error(format!("Chainlink feed {} rejected: {}", feed, e), None)
+    })?;
+
+    Ok(ChainlinkPrice {
+        feed,
+        value: round.answer.into_raw(),
+        decimals,
+        updated_at,
+        round_id: round.roundId.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(updated_at: u64) -> RoundData {
+        RoundData {
+            round_id: 10,
+            answer_positive: true,
+            updated_at,
+            answered_in_round: 10,
+        }
+    }
+
+    #[test]
+    fn test_validate_round_fresh() {
+        assert!(validate_round(&round(1_000), 1_500, 3_600).is_ok());
+    }
+
+    #[test]
+    fn test_validate_round_stale() {
+        assert!(validate_round(&round(1_000), 10_000, 3_600).is_err());
+    }
+
+    #[test]
+    fn test_default_max_age_by_heartbeat() {
+        use crate::models::ARBITRUM;
+        // 1 小时心跳的喂价停更一天后不再视为新鲜
+        assert_eq!(
+            default_max_age(&MAINNET, MAINNET.weth),
+            DEFAULT_MAX_PRICE_AGE_SECS
+        );
+        assert!(validate_round(&round(0), 24 * 60 * 60, DEFAULT_MAX_PRICE_AGE_SECS).is_err());
+        assert_eq!(
+            default_max_age(&MAINNET, MAINNET.usdc),
+            DAILY_FEED_MAX_AGE_SECS
+        );
+        assert_eq!(
+            default_max_age(&MAINNET, MAINNET.usdt.unwrap()),
+            DAILY_FEED_MAX_AGE_SECS
+        );
+        assert_eq!(
+            default_max_age(&ARBITRUM, ARBITRUM.usdc),
+            DEFAULT_MAX_PRICE_AGE_SECS
+        );
+    }
+
+    #[test]
+    fn test_validate_round_answered_in_old_round() {
+        let mut data = round(1_000);
+        data.answered_in_round = 9;
+        assert!(validate_round(&data, 1_500, 3_600).is_err());
+    }
+
+    #[test]
+    fn test_validate_round_non_positive_answer() {
+        let mut data = round(1_000);
+        data.answer_positive = false;
+        assert!(validate_round(&data, 1_500, 3_600).is_err());
+    }
+}
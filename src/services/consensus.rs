@@ -0,0 +1,130 @@
+use alloy::primitives::U256;
+
+/// 未指定阈值时允许的最大价格偏差（基点）
+pub const DEFAULT_MAX_DEVIATION_BPS: u32 = 200;
+
+/// 不同来源的价格统一换算到的精度
+pub const CONSENSUS_DECIMALS: u8 = 18;
+
+/// 达成共识至少需要的价格来源数量
+pub const MIN_CONSENSUS_SOURCES: usize = 2;
+
+/// 多个来源价格的共识结果，价格均已换算到 CONSENSUS_DECIMALS 位精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Consensus {
+    pub median: U256,
+    pub max_deviation_bps: u32,
+    pub flagged: bool, // 偏差超过阈值或可用来源少于 MIN_CONSENSUS_SOURCES
+}
+
+/// 计算中位数和最大偏差并判断是否标记，没有任何可用价格时返回 None
+pub fn evaluate(values: &[U256], threshold_bps: u32) -> Option<Consensus> {
+    let median = median(values)?;
+    let max_deviation_bps = max_deviation_bps(values, median);
+    Some(Consensus {
+        median,
+        max_deviation_bps,
+        flagged: max_deviation_bps > threshold_bps || values.len() < MIN_CONSENSUS_SOURCES,
+    })
+}
+
+/// 将任意精度的价格换算到 CONSENSUS_DECIMALS 位精度
+pub fn normalize(value: U256, decimals: u8) -> U256 {
+    if decimals <= CONSENSUS_DECIMALS {
+        value * U256::from(10).pow(U256::from(CONSENSUS_DECIMALS - decimals))
+    } else {
+        value / U256::from(10).pow(U256::from(decimals - CONSENSUS_DECIMALS))
+    }
+}
+
+/// 中位数，偶数个时取中间两个的平均值
+pub fn median(values: &[U256]) -> Option<U256> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / U256::from(2))
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// 各价格相对中位数的最大偏差（基点）
+pub fn max_deviation_bps(values: &[U256], median: U256) -> u32 {
+    if median.is_zero() {
+        return u32::MAX;
+    }
+    values
+        .iter()
+        .map(|value| {
+            let diff = if *value > median {
+                *value - median
+            } else {
+                median - *value
+            };
+            (diff * U256::from(10000) / median).saturating_to::<u32>()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        // 6 位精度的 USDC 和 8 位精度的 Chainlink 喂价换算后相等
+        assert_eq!(
+            normalize(U256::from(4000_000000_u64), 6),
+            normalize(U256::from(4000_00000000_u64), 8)
+        );
+        assert_eq!(normalize(U256::from(1), 18), U256::from(1));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        let odd = [U256::from(3), U256::from(1), U256::from(2)];
+        assert_eq!(median(&odd), Some(U256::from(2)));
+        let even = [U256::from(4), U256::from(1), U256::from(2), U256::from(3)];
+        assert_eq!(median(&even), Some(U256::from(2)));
+    }
+
+    #[test]
+    fn test_max_deviation_bps() {
+        let values = [U256::from(1000), U256::from(1010), U256::from(980)];
+        assert_eq!(max_deviation_bps(&values, U256::from(1000)), 200);
+        assert_eq!(max_deviation_bps(&values, U256::ZERO), u32::MAX);
+    }
+
+    #[test]
+    fn test_evaluate_flags() {
+        let usd = |price: u64| normalize(U256::from(price), 8);
+        assert_eq!(evaluate(&[], DEFAULT_MAX_DEVIATION_BPS), None);
+
+        // 三个来源偏差在阈值内
+        let agreeing = [usd(4000_00000000), usd(4040_00000000), usd(3990_00000000)];
+        let consensus = evaluate(&agreeing, DEFAULT_MAX_DEVIATION_BPS).unwrap();
+        assert_eq!(consensus.median, usd(4000_00000000));
+        assert_eq!(consensus.max_deviation_bps, 100);
+        assert!(!consensus.flagged);
+        // 同样的价格在更严格的阈值下被标记
+        assert!(evaluate(&agreeing, 50).unwrap().flagged);
+
+        // 一个来源严重偏离时中位数不受影响，但被标记
+        let outlier = [usd(4000_00000000), usd(4010_00000000), usd(2000_00000000)];
+        let consensus = evaluate(&outlier, DEFAULT_MAX_DEVIATION_BPS).unwrap();
+        assert_eq!(consensus.median, usd(4000_00000000));
+        assert_eq!(consensus.max_deviation_bps, 5000);
+        assert!(consensus.flagged);
+
+        // 只有一个来源时无法达成共识
+        let single = evaluate(&[usd(4000_00000000)], DEFAULT_MAX_DEVIATION_BPS).unwrap();
+        assert_eq!(single.max_deviation_bps, 0);
+        assert!(single.flagged);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::models::{IENSRegistry, IENSResolver};
+use alloy::hex;
+use alloy::primitives::{keccak256, Address, B256};
+use alloy::providers::DynProvider;
+use rmcp::ErrorData as McpError;
+
+/// 用户输入的地址或 ENS 名称解析后的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAddress {
+    pub address: Address,
+    pub ens_name: Option<String>, // 输入的 ENS 名称，或该地址的反向解析主名称
+}
+
+/// 通过 ENS Registry 做正向（名称 -> 地址）和反向（地址 -> 主名称）解析
+#[derive(Clone)]
+pub struct EnsResolver {
+    provider: DynProvider, // ENS 所在链（主网或测试网）的 provider
+    registry: Address,
+}
+
+impl EnsResolver {
+    pub fn new(provider: DynProvider, registry: Address) -> Self {
+        Self { provider, registry }
+    }
+
+    /// 名称 -> Registry 中登记的 resolver -> addr
+    pub async fn resolve(&self, name: &str) -> Result<Address, McpError> {
+        let node = namehash(name);
+        let Some(resolver) = self.resolver(node).await? else {
+            return Err(McpError::invalid_params(
+                format!("ENS name '{}' has no resolver", name),
+                None,
+            ));
+        };
+        let address = IENSResolver::new(resolver, &self.provider)
+            .addr(node)
+            .call()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to resolve ENS name '{}': {}", name, e),
+                    None,
+                )
+            })?;
+        if address.is_zero() {
+            return Err(McpError::invalid_params(
+                format!("ENS name '{}' does not resolve to an address", name),
+                None,
+            ));
+        }
+        Ok(address)
+    }
+
+    /// 查询 `<addr>.addr.reverse` 的主名称，并确认该名称正向解析回同一地址
+    pub async fn lookup(&self, address: Address) -> Result<Option<String>, McpError> {
+        let node = namehash(&format!("{}.addr.reverse", hex::encode(address)));
+        let Some(resolver) = self.resolver(node).await? else {
+            return Ok(None);
+        };
+        let name = IENSResolver::new(resolver, &self.provider)
+            .name(node)
+            .call()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(
+                    format!("Failed to look up ENS name of {}: {}", address, e),
+                    None,
+                )
+            })?;
+        if name.is_empty() {
+            return Ok(None);
+        }
+        // 反向记录可以由任何人随意设置，只有正向解析一致时才可信
+        match self.resolve(&name).await {
+            Ok(resolved) if resolved == address => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn resolver(&self, node: B256) -> Result<Option<Address>, McpError> {
+        let resolver = IENSRegistry::new(self.registry, &self.provider)
+            .resolver(node)
+            .call()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to query ENS registry: {}", e), None)
+            })?;
+        Ok((!resolver.is_zero()).then_some(resolver))
+    }
+}
+
+/// 不是十六进制地址且包含 `.` 的输入按 ENS 名称处理
+pub fn is_ens_name(input: &str) -> bool {
+    input.contains('.') && input.split('.').all(|label| !label.is_empty())
+}
+
+/// 规范化名称：转为 ASCII 小写
+///
+/// 只支持 ASCII 名称：ENSIP-15 对 Unicode 名称的映射和混淆检查没有实现，简单小写可能得到错误的 namehash，
+/// 因此非 ASCII 名称以及 ENSIP-15 不允许的 ASCII 字符直接拒绝，调用方可以改传解析后的地址
+pub fn normalize(name: &str) -> Result<String, McpError> {
+    let name = name.trim();
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '$')))
+    {
+        let reason = if c.is_ascii() {
+            format!("character '{}' is not allowed", c)
+        } else {
+            "non-ASCII names are not supported, pass the address instead".to_string()
+        };
+        return Err(McpError::invalid_params(
+            format!("Invalid ENS name '{}': {}", name, reason),
+            None,
+        ));
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+/// EIP-137 namehash
+pub fn namehash(name: &str) -> B256 {
+    let mut node = B256::ZERO;
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.rsplit('.') {
+        node = keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat());
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::b256;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth"),
+            b256!("0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" Vitalik.ETH ").unwrap(), "vitalik.eth");
+        assert_eq!(normalize("my-name_1.eth").unwrap(), "my-name_1.eth");
+        // 非 ASCII 名称需要 ENSIP-15 规范化，不做简单小写
+        assert!(normalize("VİTALİK.eth").is_err());
+        assert!(normalize("ｖitalik.eth").is_err());
+        assert!(normalize("vita lik.eth").is_err());
+    }
+
+    #[test]
+    fn test_is_ens_name() {
+        assert!(is_ens_name("vitalik.eth"));
+        assert!(is_ens_name("pay.vitalik.eth"));
+        assert!(!is_ens_name("vitalik"));
+        assert!(!is_ens_name("vitalik..eth"));
+        assert!(!is_ens_name(".eth"));
+    }
+}
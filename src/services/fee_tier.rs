@@ -0,0 +1,240 @@
+use crate::models::{ChainAddresses, IQuoterV2, IUniswapV3Factory};
+use alloy::primitives::aliases::U24;
+use alloy::primitives::{Address, U160, U256};
+use alloy::providers::Provider;
+use futures::future::join_all;
+use rmcp::ErrorData as McpError;
+
+/// Uniswap V3 的标准费率档位（单位：百万分之一）
+pub const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+/// 单个 V3 池子的报价结果
+#[derive(Debug, Clone)]
+pub struct V3Quote {
+    pub fee: u32,
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub gas_estimate: U256,
+}
+
+/// 通过 Factory 查询两个代币之间实际存在池子的费率档位
+pub async fn available_fee_tiers<P: Provider>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_a: Address,
+    token_b: Address,
+) -> Result<Vec<u32>, McpError> {
+    let factory = IUniswapV3Factory::new(chain.v3_factory, provider);
+    let lookups = join_all(FEE_TIERS.iter().map(|&fee| {
+        let factory = &factory;
+        async move {
+            let pool = factory
+                .getPool(token_a, token_b, U24::from(fee))
+                .call()
+                .await;
+            (fee, pool)
+        }
+    }))
+    .await;
+
+    let mut tiers = Vec::new();
+    for (fee, pool) in lookups {
+        let pool = pool.map_err(|e| {
+            McpError::internal_error(format!("Failed to get pool for fee {}: {}", fee, e), None)
+        })?;
+        if pool != Address::ZERO {
+            tiers.push(fee);
+        }
+    }
+    Ok(tiers)
+}
+
+/// 在指定费率档位上通过 QuoterV2 获取精确输入报价
+pub async fn quote_exact_input_single<P: Provider>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    fee: u32,
+) -> Result<V3Quote, McpError> {
+    let quoter_v2 = IQuoterV2::new(chain.quoter_v2, provider);
+    let params = IQuoterV2::QuoteExactInputSingleParams {
+        tokenIn: token_in,
+        tokenOut: token_out,
+        amountIn: amount_in,
+        fee: U24::from(fee),
+        sqrtPriceLimitX96: U160::ZERO,
+    };
+
+    let res = quoter_v2
+        .quoteExactInputSingle(params)
+        .call()
+        .await
+        .map_err(|e| {
+            McpError::internal_error(format!("Failed to get quote for fee {}: {}", fee, e), None)
+        })?;
+
+    Ok(V3Quote {
+        fee,
+        amount_in,
+        amount_out: res.amountOut,
+        gas_estimate: res.gasEstimate,
+    })
+}
+
+/// 探测所有存在的费率档位并返回到手数量最多的报价
+pub async fn best_quote_exact_input_single<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+) -> Result<V3Quote, McpError> {
+    let tiers = available_fee_tiers(provider.clone(), chain, token_in, token_out).await?;
+    if tiers.is_empty() {
+        return Err(McpError::invalid_params(
+            format!("No Uniswap V3 pool for {} / {}", token_in, token_out),
+            None,
+        ));
+    }
+
+    // 池子存在但流动性不足时报价会 revert，直接跳过该档位
+    let quotes = join_all(tiers.iter().map(|&fee| {
+        quote_exact_input_single(provider.clone(), chain, token_in, token_out, amount_in, fee)
+    }))
+    .await;
+
+    quotes
+        .into_iter()
+        .filter_map(Result::ok)
+        .max_by_key(|quote| quote.amount_out)
+        .ok_or_else(|| {
+            McpError::internal_error(
+                format!(
+                    "Failed to get quote from any fee tier ({:?}) for {} / {}",
+                    tiers, token_in, token_out
+                ),
+                None,
+            )
+        })
+}
+
+/// 指定费率时直接报价，否则自动选择最优档位
+pub async fn quote_exact_input<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    fee: Option<u32>,
+) -> Result<V3Quote, McpError> {
+    match fee {
+        Some(fee) => {
+            quote_exact_input_single(provider, chain, token_in, token_out, amount_in, fee).await
+        }
+        None => {
+            best_quote_exact_input_single(provider, chain, token_in, token_out, amount_in).await
+        }
+    }
+}
+
+/// 在指定费率档位上通过 QuoterV2 获取精确输出报价
+pub async fn quote_exact_output_single<P: Provider>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_out: U256,
+    fee: u32,
+) -> Result<V3Quote, McpError> {
+    let quoter_v2 = IQuoterV2::new(chain.quoter_v2, provider);
+    let params = IQuoterV2::QuoteExactOutputSingleParams {
+        tokenIn: token_in,
+        tokenOut: token_out,
+        amount: amount_out,
+        fee: U24::from(fee),
+        sqrtPriceLimitX96: U160::ZERO,
+    };
+
+    let res = quoter_v2
+        .quoteExactOutputSingle(params)
+        .call()
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                format!("Failed to get exact output quote for fee {}: {}", fee, e),
+                None,
+            )
+        })?;
+
+    Ok(V3Quote {
+        fee,
+        amount_in: res.amountIn,
+        amount_out,
+        gas_estimate: res.gasEstimate,
+    })
+}
+
+/// 探测所有存在的费率档位并返回花费最少的精确输出报价
+pub async fn best_quote_exact_output_single<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_out: U256,
+) -> Result<V3Quote, McpError> {
+    let tiers = available_fee_tiers(provider.clone(), chain, token_in, token_out).await?;
+    if tiers.is_empty() {
+        return Err(McpError::invalid_params(
+            format!("No Uniswap V3 pool for {} / {}", token_in, token_out),
+            None,
+        ));
+    }
+
+    // 池子流动性不足以提供目标数量时报价会 revert，直接跳过该档位
+    let quotes = join_all(tiers.iter().map(|&fee| {
+        quote_exact_output_single(
+            provider.clone(),
+            chain,
+            token_in,
+            token_out,
+            amount_out,
+            fee,
+        )
+    }))
+    .await;
+
+    quotes
+        .into_iter()
+        .filter_map(Result::ok)
+        .min_by_key(|quote| quote.amount_in)
+        .ok_or_else(|| {
+            McpError::internal_error(
+                format!(
+                    "Failed to get exact output quote from any fee tier ({:?}) for {} / {}",
+                    tiers, token_in, token_out
+                ),
+                None,
+            )
+        })
+}
+
+/// 指定费率时直接报价，否则自动选择花费最少的档位
+pub async fn quote_exact_output<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_out: U256,
+    fee: Option<u32>,
+) -> Result<V3Quote, McpError> {
+    match fee {
+        Some(fee) => {
+            quote_exact_output_single(provider, chain, token_in, token_out, amount_out, fee).await
+        }
+        None => {
+            best_quote_exact_output_single(provider, chain, token_in, token_out, amount_out).await
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::models::{FeeSpeed, GasReport, MetaData, USD_QUOTE};
+use crate::services::consensus::CONSENSUS_DECIMALS;
+use crate::services::portfolio;
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::utils::format_units;
+use alloy::primitives::{Address, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::FeeHistory;
+use rmcp::ErrorData as McpError;
+
+/// eth_feeHistory 统计的最近区块数
+pub const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// slow/normal/fast 对应的优先费百分位
+const REWARD_PERCENTILES: [f64; 3] = [10.0, 50.0, 90.0];
+
+/// maxFeePerGas = base fee × 2 + 优先费，可承受连续 6 个满块的 base fee 上涨
+const BASE_FEE_MULTIPLIER: u128 = 2;
+
+/// 在 eth_estimateGas 结果上增加的余量（百分比），避免执行时状态变化导致 out of gas
+const GAS_LIMIT_BUFFER_PCT: u64 = 20;
+
+/// 一笔交易使用的 EIP-1559 费用
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub base_fee_per_gas: u128, // 下一个区块的 base fee
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+}
+
+impl FeeEstimate {
+    /// 按 maxFeePerGas 计算的最坏情况手续费
+    pub fn max_cost(&self, gas_limit: u64) -> U256 {
+        U256::from(gas_limit) * U256::from(self.max_fee_per_gas)
+    }
+
+    /// 按下一个区块 base fee 加优先费计算的预计手续费
+    pub fn expected_cost(&self, gas: u64) -> U256 {
+        let price =
+            (self.base_fee_per_gas + self.max_priority_fee_per_gas).min(self.max_fee_per_gas);
+        U256::from(gas) * U256::from(price)
+    }
+}
+
+fn percentile_index(speed: FeeSpeed) -> usize {
+    match speed {
+        FeeSpeed::Slow => 0,
+        FeeSpeed::Normal => 1,
+        FeeSpeed::Fast => 2,
+    }
+}
+
+/// 根据最近区块的 eth_feeHistory 估算费用
+pub async fn estimate_fees<P: Provider>(
+    provider: &P,
+    speed: FeeSpeed,
+) -> Result<FeeEstimate, McpError> {
+    let history = provider
+        .get_fee_history(
+            FEE_HISTORY_BLOCKS,
+            BlockNumberOrTag::Latest,
+            &REWARD_PERCENTILES,
+        )
+        .await
+        .map_err(|e| McpError::internal_error(format!("Failed to get fee history: {}", e), None))?;
+    fees_from_history(&history, speed)
+}
+
+/// 优先费取各区块对应百分位的中位数，忽略没有交易的空块
+pub fn fees_from_history(history: &FeeHistory, speed: FeeSpeed) -> Result<FeeEstimate, McpError> {
+    let base_fee_per_gas = history
+        .next_block_base_fee()
+        .filter(|base_fee| *base_fee > 0)
+        .ok_or_else(|| {
+            McpError::internal_error("Node returned no EIP-1559 base fee in fee history", None)
+        })?;
+    let index = percentile_index(speed);
+    let mut rewards: Vec<u128> = history
+        .reward
+        .iter()
+        .flatten()
+        .filter_map(|block| block.get(index).copied())
+        .filter(|reward| *reward > 0)
+        .collect();
+    rewards.sort_unstable();
+    // 没有可用样本时至少给 1 wei 优先费
+    let max_priority_fee_per_gas = rewards.get(rewards.len() / 2).copied().unwrap_or(1);
+    Ok(FeeEstimate {
+        base_fee_per_gas,
+        max_fee_per_gas: base_fee_per_gas * BASE_FEE_MULTIPLIER + max_priority_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
+
+pub fn gas_limit_with_buffer(gas_estimate: u64) -> u64 {
+    gas_estimate.saturating_add(gas_estimate * GAS_LIMIT_BUFFER_PCT / 100)
+}
+
+/// 汇总 gas 预估和手续费，native_usd 为原生代币的 USD 价格
+pub fn gas_report(
+    speed: FeeSpeed,
+    gas_estimate: u64,
+    gas_limit: u64,
+    fees: &FeeEstimate,
+    native_symbol: &str,
+    native_usd: Option<&MetaData>,
+) -> GasReport {
+    let cost = fees.expected_cost(gas_estimate);
+    GasReport {
+        speed,
+        gas_estimate,
+        approximate: false,
+        gas_limit,
+        base_fee_gwei: gwei(fees.base_fee_per_gas),
+        max_fee_gwei: gwei(fees.max_fee_per_gas),
+        priority_fee_gwei: gwei(fees.max_priority_fee_per_gas),
+        cost: MetaData {
+            value: cost,
+            decimals: 18,
+            symbol: native_symbol.to_string(),
+        },
+        cost_usd: native_usd.map(|price| MetaData {
+            value: portfolio::value_usd(cost, 18, price.value, price.decimals),
+            decimals: CONSENSUS_DECIMALS,
+            symbol: USD_QUOTE.to_string(),
+        }),
+    }
+}
+
+/// 合计多笔交易的预计手续费，任一笔缺少 USD 价格时不给出 USD 合计
+pub fn total_cost(reports: &[&GasReport]) -> (MetaData, Option<MetaData>) {
+    let cost = reports
+        .iter()
+        .fold(U256::ZERO, |acc, report| acc + report.cost.value);
+    let cost_usd = reports
+        .iter()
+        .map(|report| report.cost_usd.as_ref().map(|usd| usd.value))
+        .sum::<Option<U256>>();
+    let symbol = reports
+        .first()
+        .map(|report| report.cost.symbol.clone())
+        .unwrap_or_default();
+    (
+        MetaData {
+            value: cost,
+            decimals: 18,
+            symbol,
+        },
+        cost_usd.map(|value| MetaData {
+            value,
+            decimals: CONSENSUS_DECIMALS,
+            symbol: USD_QUOTE.to_string(),
+        }),
+    )
+}
+
+/// 广播前检查原生代币余额能否覆盖交易附带的 value 和按 maxFeePerGas 计算的最大 gas 费用
+pub async fn check_native_balance<P: Provider>(
+    provider: &P,
+    account: Address,
+    value: U256,
+    max_gas_cost: U256,
+    native_symbol: &str,
+) -> Result<(), McpError> {
+    let required = value + max_gas_cost;
+    let balance = provider.get_balance(account).await.map_err(|e| {
+        McpError::internal_error(format!("Failed to get native balance: {}", e), None)
+    })?;
+    if balance < required {
+        let native = |value: U256| MetaData {
+            value,
+            decimals: 18,
+            symbol: native_symbol.to_string(),
+        };
+        return Err(McpError::invalid_params(
+            format!(
+                "Insufficient {} for amount and gas: have {}, need {}",
+                native_symbol,
+                native(balance),
+                native(required)
+            ),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// wei 换算为 Gwei 字符串
+pub fn gwei(wei: u128) -> String {
+    format_units(U256::from(wei), "gwei").unwrap_or_else(|_| "0".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_history(base_fees: Vec<u128>, reward: Vec<Vec<u128>>) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees,
+            reward: Some(reward),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fees_from_history() {
+        let gwei = 1_000_000_000;
+        let history = fee_history(
+            vec![10 * gwei, 12 * gwei, 11 * gwei],
+            vec![
+                vec![gwei, 2 * gwei, 5 * gwei],
+                vec![0, 0, 0], // 空块
+                vec![gwei, 3 * gwei, 9 * gwei],
+            ],
+        );
+        let normal = fees_from_history(&history, FeeSpeed::Normal).unwrap();
+        assert_eq!(normal.base_fee_per_gas, 11 * gwei);
+        assert_eq!(normal.max_priority_fee_per_gas, 3 * gwei);
+        assert_eq!(normal.max_fee_per_gas, 25 * gwei);
+        let fast = fees_from_history(&history, FeeSpeed::Fast).unwrap();
+        assert_eq!(fast.max_priority_fee_per_gas, 9 * gwei);
+        let slow = fees_from_history(&history, FeeSpeed::Slow).unwrap();
+        assert_eq!(slow.max_priority_fee_per_gas, gwei);
+
+        assert!(fees_from_history(&fee_history(vec![0, 0], vec![]), FeeSpeed::Normal).is_err());
+    }
+
+    #[test]
+    fn test_gas_report_cost() {
+        let fees = FeeEstimate {
+            base_fee_per_gas: 10_000_000_000,
+            max_fee_per_gas: 22_000_000_000,
+            max_priority_fee_per_gas: 2_000_000_000,
+        };
+        // 21000 gas × 12 Gwei = 0.000252 ETH，ETH 价格 4000 USD（8 位精度）
+        let price = MetaData {
+            value: U256::from(4000_00000000_u64),
+            decimals: 8,
+            symbol: USD_QUOTE.to_string(),
+        };
+        let report = gas_report(FeeSpeed::Normal, 21_000, 25_200, &fees, "ETH", Some(&price));
+        assert_eq!(report.cost.value, U256::from(252_000_000_000_000_u64));
+        assert_eq!(
+            report.cost_usd.as_ref().unwrap().value,
+            portfolio::usd_amount(1.008)
+        );
+        assert_eq!(report.max_fee_gwei, "22.000000000");
+        assert_eq!(fees.max_cost(25_200), U256::from(554_400_000_000_000_u64));
+        assert_eq!(gas_limit_with_buffer(21_000), 25_200);
+
+        let approval = gas_report(FeeSpeed::Normal, 46_000, 55_200, &fees, "ETH", Some(&price));
+        let (total, total_usd) = total_cost(&[&report, &approval]);
+        assert_eq!(total.value, report.cost.value + approval.cost.value);
+        assert_eq!(
+            total_usd.unwrap().value,
+            report.cost_usd.as_ref().unwrap().value + approval.cost_usd.as_ref().unwrap().value
+        );
+        let unpriced = gas_report(FeeSpeed::Normal, 46_000, 55_200, &fees, "ETH", None);
+        assert!(total_cost(&[&report, &unpriced]).1.is_none());
+    }
+}
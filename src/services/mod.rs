@@ -1,3 +1,19 @@
+pub mod approval;
+pub mod block_resolver;
+pub mod chain_context;
+pub mod chainlink;
+pub mod consensus;
+pub mod ens;
+pub mod fee_tier;
+pub mod gas;
+pub mod pool_price;
+pub mod portfolio;
+pub mod receipt_events;
+pub mod routing;
+pub mod rpc;
+pub mod token_discovery;
+pub mod token_registry;
 pub mod wallet_service;
 
+pub use chain_context::ChainContext;
 pub use wallet_service::WalletService;
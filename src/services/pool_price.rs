@@ -0,0 +1,271 @@
+use crate::models::{ChainAddresses, IUniswapV3Factory, IUniswapV3Pool};
+use crate::services::fee_tier::FEE_TIERS;
+use alloy::primitives::aliases::U24;
+use alloy::primitives::{Address, U256, U512};
+use alloy::providers::Provider;
+use futures::future::join_all;
+use rmcp::ErrorData as McpError;
+
+/// 未指定窗口时 TWAP 使用的时间窗口（秒）
+pub const DEFAULT_TWAP_WINDOW_SECS: u32 = 1800;
+
+/// TickMath 支持的最小/最大 tick
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+
+/// TickMath.getSqrtRatioAtTick 中 1.0001^(-2^i / 2) 的 Q128 常量
+const SQRT_RATIO_FACTORS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// 从池子状态计算出的价格
+#[derive(Debug, Clone)]
+pub struct PoolPrice {
+    pub pool: Address,
+    pub fee: u32,
+    pub amount_out: U256, // base_amount 个基础代币对应的计价代币数量
+}
+
+/// 移植自 Uniswap V3 TickMath.getSqrtRatioAtTick，返回 sqrt(1.0001^tick) * 2^96
+pub fn sqrt_ratio_at_tick(tick: i32) -> U256 {
+    let abs_tick = tick.unsigned_abs();
+    assert!(abs_tick <= MAX_TICK as u32, "tick out of range");
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001_u128)
+    } else {
+        U256::from(1) << 128
+    };
+    for (i, factor) in SQRT_RATIO_FACTORS.iter().enumerate() {
+        if abs_tick & (1 << (i + 1)) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 转为 Q64.96，向上取整
+    let remainder = ratio & U256::from(u32::MAX);
+    (ratio >> 32) + U256::from(!remainder.is_zero() as u8)
+}
+
+/// 按 sqrtPriceX96 计算 base_amount 个基础代币可以换到的计价代币数量（原始单位）
+pub fn quote_at_sqrt_price(sqrt_price_x96: U256, base_amount: U256, base_is_token0: bool) -> U256 {
+    // sqrtPriceX96 的平方最多 320 位，在 512 位下计算避免溢出
+    let sqrt_price = U512::from(sqrt_price_x96);
+    let ratio_x192 = sqrt_price * sqrt_price;
+    let base_amount = U512::from(base_amount);
+    let quote = if base_is_token0 {
+        (base_amount * ratio_x192) >> 192
+    } else {
+        (base_amount << 192) / ratio_x192
+    };
+    quote.saturating_to::<U256>()
+}
+
+/// 由 tickCumulative 差值计算时间加权平均 tick，负数向负无穷取整（与 OracleLibrary 一致）
+pub fn twap_tick(tick_cumulative_delta: i64, window_secs: u32) -> i32 {
+    let window = i64::from(window_secs);
+    let mut tick = tick_cumulative_delta / window;
+    if tick_cumulative_delta < 0 && tick_cumulative_delta % window != 0 {
+        tick -= 1;
+    }
+    tick as i32
+}
+
+/// 指定费率时使用该档位的池子，否则选择流动性最大的池子
+async fn select_pool<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_a: Address,
+    token_b: Address,
+    fee: Option<u32>,
+) -> Result<(Address, u32), McpError> {
+    let fees = match fee {
+        Some(fee) => vec![fee],
+        None => FEE_TIERS.to_vec(),
+    };
+    let factory = IUniswapV3Factory::new(chain.v3_factory, provider.clone());
+    let pools = join_all(fees.iter().map(|&fee| {
+        let factory = &factory;
+        let provider = provider.clone();
+        async move {
+            let pool = factory
+                .getPool(token_a, token_b, U24::from(fee))
+                .call()
+                .await
+                .ok()
+                .filter(|pool| *pool != Address::ZERO)?;
+            let liquidity = IUniswapV3Pool::new(pool, provider)
+                .liquidity()
+                .call()
+                .await
+                .ok()?;
+            Some((pool, fee, liquidity))
+        }
+    }))
+    .await;
+
+    pools
+        .into_iter()
+        .flatten()
+        .max_by_key(|(_, _, liquidity)| *liquidity)
+        .map(|(pool, fee, _)| (pool, fee))
+        .ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "No Uniswap V3 pool for {} / {} (fee tiers {:?})",
+                    token_a, token_b, fees
+                ),
+                None,
+            )
+        })
+}
+
+/// 判断基础代币是否为池子的 token0
+async fn base_is_token0<P: Provider>(
+    pool: &IUniswapV3Pool::IUniswapV3PoolInstance<P>,
+    base: Address,
+) -> Result<bool, McpError> {
+    let token0 =
+        pool.token0().call().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get pool token0: {}", e), None)
+        })?;
+    Ok(token0 == base)
+}
+
+/// 读取 slot0 中的 sqrtPriceX96 计算即时价格
+pub async fn spot_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    base: Address,
+    quote: Address,
+    base_amount: U256,
+    fee: Option<u32>,
+) -> Result<PoolPrice, McpError> {
+    let (pool_address, fee) = select_pool(provider.clone(), chain, base, quote, fee).await?;
+    let pool = IUniswapV3Pool::new(pool_address, provider);
+    let slot0 =
+        pool.slot0().call().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get pool slot0: {}", e), None)
+        })?;
+    let sqrt_price_x96 = U256::from(slot0.sqrtPriceX96);
+    if sqrt_price_x96.is_zero() {
+        return Err(McpError::internal_error(
+            format!("Pool {} is not initialized", pool_address),
+            None,
+        ));
+    }
+    let base_is_token0 = base_is_token0(&pool, base).await?;
+
+    Ok(PoolPrice {
+        pool: pool_address,
+        fee,
+        amount_out: quote_at_sqrt_price(sqrt_price_x96, base_amount, base_is_token0),
+    })
+}
+
+/// 通过 observe() 读取 window_secs 内的 tickCumulative，计算时间加权平均价格
+pub async fn twap_price<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    base: Address,
+    quote: Address,
+    base_amount: U256,
+    fee: Option<u32>,
+    window_secs: u32,
+) -> Result<PoolPrice, McpError> {
+    if window_secs == 0 {
+        return Err(McpError::invalid_params(
+            "TWAP window must be greater than 0 seconds",
+            None,
+        ));
+    }
+    let (pool_address, fee) = select_pool(provider.clone(), chain, base, quote, fee).await?;
+    let pool = IUniswapV3Pool::new(pool_address, provider);
+
+    // 观测记录不足以覆盖窗口时合约会以 OLD revert
+    let observation = pool
+        .observe(vec![window_secs, 0])
+        .call()
+        .await
+        .map_err(|e| {
+            McpError::invalid_params(
+                format!(
+                    "Pool {} cannot provide a {}s TWAP (oracle history too short?): {}",
+                    pool_address, window_secs, e
+                ),
+                None,
+            )
+        })?;
+    let [start, end] = observation.tickCumulatives[..] else {
+        return Err(McpError::internal_error(
+            "observe() returned an unexpected number of observations",
+            None,
+        ));
+    };
+    let tick = twap_tick((end - start).as_i64(), window_secs);
+    let base_is_token0 = base_is_token0(&pool, base).await?;
+
+    Ok(PoolPrice {
+        pool: pool_address,
+        fee,
+        amount_out: quote_at_sqrt_price(sqrt_ratio_at_tick(tick), base_amount, base_is_token0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_bounds() {
+        assert_eq!(sqrt_ratio_at_tick(0), U256::from(1) << 96);
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK), U256::from(4295128739_u64));
+        assert_eq!(
+            sqrt_ratio_at_tick(MAX_TICK),
+            U256::from_str_radix("1461446703485210103287273052203988822378723970342", 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quote_at_sqrt_price_orders_tokens() {
+        // sqrtPrice = 2 * 2^96，即 1 token0 = 4 token1
+        let sqrt_price = U256::from(2) << 96;
+        let amount = U256::from(1_000_000);
+        assert_eq!(
+            quote_at_sqrt_price(sqrt_price, amount, true),
+            U256::from(4_000_000)
+        );
+        assert_eq!(
+            quote_at_sqrt_price(sqrt_price, amount, false),
+            U256::from(250_000)
+        );
+    }
+
+    #[test]
+    fn test_twap_tick_rounds_toward_negative_infinity() {
+        assert_eq!(twap_tick(3600, 1800), 2);
+        assert_eq!(twap_tick(-3601, 1800), -3);
+        assert_eq!(twap_tick(-3600, 1800), -2);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::services::consensus::{self, CONSENSUS_DECIMALS};
+use alloy::primitives::utils::parse_units;
+use alloy::primitives::U256;
+
+/// 未指定时低于该 USD 价值的持仓视为粉尘
+pub const DEFAULT_MIN_VALUE_USD: f64 = 1.0;
+
+/// 持仓的 USD 价值，精度为 CONSENSUS_DECIMALS
+pub fn value_usd(amount: U256, decimals: u8, price: U256, price_decimals: u8) -> U256 {
+    amount.saturating_mul(consensus::normalize(price, price_decimals))
+        / U256::from(10).pow(U256::from(decimals))
+}
+
+/// 占总价值的比例（基点）
+pub fn share_bps(value: U256, total: U256) -> u32 {
+    if total.is_zero() {
+        return 0;
+    }
+    (value.saturating_mul(U256::from(10000)) / total).saturating_to::<u32>()
+}
+
+/// 将以 USD 表示的金额换算为 CONSENSUS_DECIMALS 精度，负数或无效值按 0 处理
+pub fn usd_amount(value: f64) -> U256 {
+    if !value.is_finite() || value <= 0.0 {
+        return U256::ZERO;
+    }
+    parse_units(&value.to_string(), CONSENSUS_DECIMALS)
+        .map(Into::into)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_usd() {
+        // 1.5 个 18 位精度的代币，价格 4000 USDC（6 位精度）
+        let amount = U256::from(15) * U256::from(10).pow(U256::from(17));
+        let value = value_usd(amount, 18, U256::from(4000_000000_u64), 6);
+        assert_eq!(value, usd_amount(6000.0));
+        // 100 USDC，Chainlink 价格 1.00000000（8 位精度）
+        let value = value_usd(U256::from(100_000000_u64), 6, U256::from(1_00000000_u64), 8);
+        assert_eq!(value, usd_amount(100.0));
+    }
+
+    #[test]
+    fn test_share_bps() {
+        assert_eq!(share_bps(U256::from(1), U256::from(4)), 2500);
+        assert_eq!(share_bps(U256::from(1), U256::ZERO), 0);
+    }
+
+    #[test]
+    fn test_usd_amount() {
+        assert_eq!(usd_amount(1.0), U256::from(10).pow(U256::from(18)));
+        assert_eq!(usd_amount(-1.0), U256::ZERO);
+        assert_eq!(usd_amount(f64::NAN), U256::ZERO);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::models::{IUniswapV2Pair, IUniswapV3Pool, SwapProtocol, IERC20};
+use alloy::primitives::{hex, Address, U256};
+use alloy::rpc::types::Log;
+use alloy::sol_types::{decode_revert_reason, SolEvent};
+
+/// 从回执日志中识别出的事件，数量还没有按代币精度格式化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptEvent {
+    Transfer {
+        token: Address,
+        from: Address,
+        to: Address,
+        value: U256,
+    },
+    Swap(PoolSwap),
+}
+
+/// Uniswap 池子的一次交换，V2 和 V3 统一为 token0/token1 的流入流出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolSwap {
+    pub protocol: SwapProtocol,
+    pub pool: Address,
+    pub sender: Address,
+    pub recipient: Address,
+    pub zero_for_one: bool, // true 表示 token0 流入池子、token1 流出
+    pub amount_in: U256,
+    pub amount_out: U256,
+}
+
+/// 按日志顺序解析 ERC20 Transfer 和 Uniswap V2/V3 Swap，其余日志忽略
+pub fn decode_events(logs: &[Log]) -> Vec<ReceiptEvent> {
+    logs.iter().filter_map(decode_event).collect()
+}
+
+fn decode_event(log: &Log) -> Option<ReceiptEvent> {
+    let topic0 = *log.topic0()?;
+    if topic0 == IERC20::Transfer::SIGNATURE_HASH {
+        // ERC721 的 tokenId 也是 indexed，有 4 个 topic
+        if log.topics().len() != 3 {
+            return None;
+        }
+        let transfer = log.log_decode::<IERC20::Transfer>().ok()?.inner;
+        return Some(ReceiptEvent::Transfer {
+            token: transfer.address,
+            from: transfer.data.from,
+            to: transfer.data.to,
+            value: transfer.data.value,
+        });
+    }
+    if topic0 == IUniswapV3Pool::Swap::SIGNATURE_HASH {
+        let swap = log.log_decode::<IUniswapV3Pool::Swap>().ok()?.inner;
+        // 正数表示流入池子，负数表示流出池子
+        let zero_for_one = swap.data.amount0.is_positive();
+        let (amount_in, amount_out) = if zero_for_one {
+            (swap.data.amount0, swap.data.amount1)
+        } else {
+            (swap.data.amount1, swap.data.amount0)
+        };
+        return Some(ReceiptEvent::Swap(PoolSwap {
+            protocol: SwapProtocol::V3,
+            pool: swap.address,
+            sender: swap.data.sender,
+            recipient: swap.data.recipient,
+            zero_for_one,
+            amount_in: amount_in.unsigned_abs(),
+            amount_out: amount_out.unsigned_abs(),
+        }));
+    }
+    if topic0 == IUniswapV2Pair::Swap::SIGNATURE_HASH {
+        let swap = log.log_decode::<IUniswapV2Pair::Swap>().ok()?.inner;
+        let zero_for_one = !swap.data.amount0In.is_zero();
+        let (amount_in, amount_out) = if zero_for_one {
+            (swap.data.amount0In, swap.data.amount1Out)
+        } else {
+            (swap.data.amount1In, swap.data.amount0Out)
+        };
+        return Some(ReceiptEvent::Swap(PoolSwap {
+            protocol: SwapProtocol::V2,
+            pool: swap.address,
+            sender: swap.data.sender,
+            recipient: swap.data.to,
+            zero_for_one,
+            amount_in,
+            amount_out,
+        }));
+    }
+    None
+}
+
+/// 解析 eth_call 返回的 revert 数据：Error(string)、Panic(uint256)，无法识别的自定义错误给出 selector
+pub fn revert_reason(data: &[u8]) -> Option<String> {
+    if data.is_empty() {
+        return None;
+    }
+    decode_revert_reason(data).or_else(|| {
+        data.get(..4)
+            .map(|selector| format!("custom error 0x{}", hex::encode(selector)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, I256};
+    use alloy::sol_types::{Revert, SolError};
+
+    fn log<E: SolEvent>(address: Address, event: &E) -> Log {
+        Log {
+            inner: alloy::primitives::Log {
+                address,
+                data: event.encode_log_data(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let token = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let pool = address!("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640");
+        let wallet = address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        let transfer = IERC20::Transfer {
+            from: pool,
+            to: wallet,
+            value: U256::from(3000),
+        };
+        let v3_swap = IUniswapV3Pool::Swap {
+            sender: wallet,
+            recipient: wallet,
+            amount0: I256::try_from(-3000).unwrap(),
+            amount1: I256::try_from(1).unwrap(),
+            sqrtPriceX96: Default::default(),
+            liquidity: 0,
+            tick: Default::default(),
+        };
+        let v2_swap = IUniswapV2Pair::Swap {
+            sender: wallet,
+            amount0In: U256::from(5),
+            amount1In: U256::ZERO,
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(7),
+            to: wallet,
+        };
+        // ERC721 Transfer 多一个 indexed tokenId，应被忽略
+        let mut nft = log(token, &transfer);
+        nft.inner.data = alloy::primitives::LogData::new_unchecked(
+            [nft.topics().to_vec(), vec![Default::default()]].concat(),
+            Default::default(),
+        );
+
+        let events = decode_events(&[
+            log(token, &transfer),
+            nft,
+            log(pool, &v3_swap),
+            log(pool, &v2_swap),
+        ]);
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            ReceiptEvent::Transfer {
+                token,
+                from: pool,
+                to: wallet,
+                value: U256::from(3000),
+            }
+        );
+        let ReceiptEvent::Swap(v3) = &events[1] else {
+            panic!("expected swap");
+        };
+        assert_eq!(v3.protocol, SwapProtocol::V3);
+        assert!(!v3.zero_for_one);
+        assert_eq!(v3.amount_in, U256::from(1));
+        assert_eq!(v3.amount_out, U256::from(3000));
+        let ReceiptEvent::Swap(v2) = &events[2] else {
+            panic!("expected swap");
+        };
+        assert_eq!(v2.protocol, SwapProtocol::V2);
+        assert!(v2.zero_for_one);
+        assert_eq!(
+            (v2.amount_in, v2.amount_out),
+            (U256::from(5), U256::from(7))
+        );
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let data = Revert::from("STF").abi_encode();
+        assert_eq!(revert_reason(&data).unwrap(), "revert: STF");
+        assert_eq!(
+            revert_reason(&[0xde, 0xad, 0xbe, 0xef, 0x00]).unwrap(),
+            "custom error 0xdeadbeef"
+        );
+        assert!(revert_reason(&[]).is_none());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::models::{ChainAddresses, IQuoterV2};
+use crate::services::fee_tier;
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::providers::Provider;
+use futures::future::join_all;
+use rmcp::ErrorData as McpError;
+
+/// 一条 V3 兑换路径及其报价
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub tokens: Vec<Address>, // 依次经过的代币，首尾为输入和输出代币
+    pub fees: Vec<u32>,       // 每一跳使用的费率档位，长度为 tokens.len() - 1
+    pub amount_in: U256,
+    pub amount_out: U256,
+    pub gas_estimate: U256,
+}
+
+impl Route {
+    pub fn is_single_hop(&self) -> bool {
+        self.fees.len() == 1
+    }
+
+    /// 按 Uniswap V3 的 path 格式编码：token(20) | fee(3) | token(20) | ...
+    pub fn encode_path(&self) -> Bytes {
+        encode_path(&self.tokens, &self.fees)
+    }
+}
+
+pub fn encode_path(tokens: &[Address], fees: &[u32]) -> Bytes {
+    let mut path = Vec::with_capacity(tokens.len() * 20 + fees.len() * 3);
+    for (i, token) in tokens.iter().enumerate() {
+        path.extend_from_slice(token.as_slice());
+        if let Some(fee) = fees.get(i) {
+            path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        }
+    }
+    Bytes::from(path)
+}
+
+/// 直连路径以及经过每个中间代币的两跳路径
+pub fn candidate_paths(
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+) -> Vec<Vec<Address>> {
+    let mut paths = vec![vec![token_in, token_out]];
+    for hub in chain.hub_tokens() {
+        if hub != token_in && hub != token_out {
+            paths.push(vec![token_in, hub, token_out]);
+        }
+    }
+    paths
+}
+
+/// 为给定代币序列逐跳选择最优费率档位并报价
+pub async fn quote_path<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    tokens: Vec<Address>,
+    amount_in: U256,
+) -> Result<Route, McpError> {
+    let mut fees = Vec::with_capacity(tokens.len() - 1);
+    let mut amount = amount_in;
+    let mut gas_estimate = U256::ZERO;
+    for hop in tokens.windows(2) {
+        let quote = fee_tier::best_quote_exact_input_single(
+            provider.clone(),
+            chain,
+            hop[0],
+            hop[1],
+            amount,
+        )
+        .await?;
+        fees.push(quote.fee);
+        amount = quote.amount_out;
+        gas_estimate += quote.gas_estimate;
+    }
+
+    // 多跳时按完整路径再报价一次，确认编码后的路径可以直接用于 exactInput
+    if fees.len() > 1 {
+        let res = IQuoterV2::new(chain.quoter_v2, provider)
+            .quoteExactInput(encode_path(&tokens, &fees), amount_in)
+            .call()
+            .await
+            .map_err(|e| {
+                McpError::internal_error(format!("Failed to get multi-hop quote: {}", e), None)
+            })?;
+        amount = res.amountOut;
+        gas_estimate = res.gasEstimate;
+    }
+
+    Ok(Route {
+        tokens,
+        fees,
+        amount_in,
+        amount_out: amount,
+        gas_estimate,
+    })
+}
+
+/// 在直连和经过链上中间代币的两跳路径中选择到手数量最多的路径
+pub async fn best_route<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+) -> Result<Route, McpError> {
+    let routes = join_all(
+        candidate_paths(chain, token_in, token_out)
+            .into_iter()
+            .map(|tokens| quote_path(provider.clone(), chain, tokens, amount_in)),
+    )
+    .await;
+
+    routes
+        .into_iter()
+        .filter_map(Result::ok)
+        .max_by_key(|route| route.amount_out)
+        .ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "No Uniswap V3 route found for {} / {}, including through hub tokens",
+                    token_in, token_out
+                ),
+                None,
+            )
+        })
+}
+
+/// 指定费率时只在该档位上直连报价，否则搜索最优路径
+pub async fn quote_route<P: Provider + Clone>(
+    provider: P,
+    chain: &ChainAddresses,
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+    fee: Option<u32>,
+) -> Result<Route, McpError> {
+    match fee {
+        Some(fee) => {
+            let quote = fee_tier::quote_exact_input_single(
+                provider, chain, token_in, token_out, amount_in, fee,
+            )
+            .await?;
+            Ok(Route {
+                tokens: vec![token_in, token_out],
+                fees: vec![quote.fee],
+                amount_in,
+                amount_out: quote.amount_out,
+                gas_estimate: quote.gas_estimate,
+            })
+        }
+        None => best_route(provider, chain, token_in, token_out, amount_in).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MAINNET;
+    use alloy::primitives::address;
+
+    #[test]
+    fn test_encode_path_single_hop() {
+        let path = encode_path(&[MAINNET.weth, MAINNET.usdc], &[500]);
+        assert_eq!(path.len(), 43);
+        assert_eq!(&path[..20], MAINNET.weth.as_slice());
+        assert_eq!(&path[20..23], &[0x00, 0x01, 0xf4]);
+        assert_eq!(&path[23..], MAINNET.usdc.as_slice());
+    }
+
+    #[test]
+    fn test_encode_path_multi_hop() {
+        let token = address!("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599");
+        let path = encode_path(&[token, MAINNET.weth, MAINNET.usdc], &[3000, 10000]);
+        assert_eq!(path.len(), 66);
+        assert_eq!(&path[20..23], &[0x00, 0x0b, 0xb8]);
+        assert_eq!(&path[43..46], &[0x00, 0x27, 0x10]);
+    }
+
+    #[test]
+    fn test_candidate_paths_skip_hub_endpoints() {
+        let token = address!("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599");
+        let paths = candidate_paths(&MAINNET, token, MAINNET.weth);
+        assert_eq!(paths[0], vec![token, MAINNET.weth]);
+        assert_eq!(paths.len(), MAINNET.hub_tokens().len());
+        assert!(paths
+            .iter()
+            .all(|path| path.len() <= 2 || path[1] != MAINNET.weth));
+    }
+}
@@ -0,0 +1,701 @@
+use crate::config::RpcConfig;
+use alloy::primitives::{keccak256, Bytes, U64};
+use alloy::providers::{DynProvider, Provider, RootProvider};
+use alloy::rpc::client::{BuiltInConnectionString, ClientBuilder, RpcClient};
+use alloy::rpc::json_rpc::{
+    Id, Request, RequestPacket, Response, ResponsePacket, ResponsePayload, RpcError,
+    SerializedRequest,
+};
+use alloy::transports::layers::RetryBackoffLayer;
+use alloy::transports::{
+    BoxTransport, TransportConnect, TransportError, TransportErrorKind, TransportFut,
+};
+use anyhow::{bail, Context as _, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+/// 开启 quorum 时需要多个节点结果一致的只读方法（余额、balanceOf 和报价都走这两个方法）及其区块参数的位置
+const QUORUM_METHODS: &[(&str, usize)] = &[("eth_call", 1), ("eth_getBalance", 1)];
+
+/// 广播交易的方法：换节点重发时，前一个节点可能已经收到并转发了这笔交易
+const SEND_RAW_TRANSACTION: &str = "eth_sendRawTransaction";
+
+/// 为每个 RPC 请求加上超时的 transport 层
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+}
+
+impl TimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<S> Layer<S> for TimeoutLayer {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S> Service<RequestPacket> for TimeoutService<S>
+where
+    S: Service<
+            RequestPacket,
+            Response = ResponsePacket,
+            Error = TransportError,
+            Future = TransportFut<'static>,
+        > + Send
+        + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let timeout = self.timeout;
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            tokio::time::timeout(timeout, response)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(TransportErrorKind::custom_str(&format!(
+                        "RPC request timed out after {}s",
+                        timeout.as_secs()
+                    )))
+                })
+        })
+    }
+}
+
+/// 单个 RPC 节点及其健康状态
+struct Endpoint {
+    url: String,
+    transport: TimeoutService<BoxTransport>,
+    stats: Mutex<EndpointStats>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EndpointStats {
+    healthy: bool,             // 最近一次请求或健康检查是否成功
+    latency: Option<Duration>, // 响应时间的指数移动平均
+}
+
+impl Endpoint {
+    fn stats(&self) -> EndpointStats {
+        *self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn track_success(&self, elapsed: Duration) {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.healthy = true;
+        stats.latency = Some(ewma(stats.latency, elapsed));
+    }
+
+    fn track_failure(&self, error: &TransportError) {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        if stats.healthy {
+            tracing::warn!("RPC endpoint {} marked unhealthy: {}", self.url, error);
+        }
+        stats.healthy = false;
+    }
+
+    /// 发送请求并记录延迟；限流等可切换的 JSON-RPC 错误响应转成 Err
+    async fn call(&self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let started = Instant::now();
+        let result = match self.transport.clone().call(request).await {
+            Ok(response) => match response.as_error() {
+                Some(error) if error.is_retry_err() => Err(RpcError::ErrorResp(error.clone())),
+                _ => Ok(response),
+            },
+            Err(error) => Err(error),
+        };
+        match &result {
+            Ok(_) => self.track_success(started.elapsed()),
+            Err(error) if should_fail_over(error) => self.track_failure(error),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// 换到本节点重发交易时，前一个节点可能已经广播成功：
+    /// already known 直接视为成功；nonce too low 时能在本节点查到这笔交易才视为成功，否则原样返回错误
+    async fn already_broadcast(
+        &self,
+        request: &RequestPacket,
+        response: ResponsePacket,
+    ) -> ResponsePacket {
+        let Some(hash) = raw_transaction_hash(request) else {
+            return response;
+        };
+        let Some((known, nonce_too_low)) = response
+            .as_error()
+            .map(|error| is_already_broadcast_error(&error.message))
+        else {
+            return response;
+        };
+        let broadcast = known || (nonce_too_low && self.has_transaction(&hash).await);
+        let ResponsePacket::Single(Response { id, .. }) = &response else {
+            return response;
+        };
+        match RawValue::from_string(format!("\"{}\"", hash)) {
+            Ok(result) if broadcast => {
+                tracing::info!(
+                    "Transaction {} was already broadcast before failing over to {}",
+                    hash,
+                    self.url
+                );
+                ResponsePacket::Single(Response {
+                    id: id.clone(),
+                    payload: ResponsePayload::Success(result),
+                })
+            }
+            _ => response,
+        }
+    }
+
+    async fn has_transaction(&self, hash: &str) -> bool {
+        let Ok(request) = Request::new(
+            "eth_getTransactionByHash",
+            Id::Number(0),
+            [hash.to_string()],
+        )
+        .serialize()
+        .map(RequestPacket::Single) else {
+            return false;
+        };
+        match self.call(request).await {
+            Ok(ResponsePacket::Single(Response {
+                payload: ResponsePayload::Success(result),
+                ..
+            })) => result.get() != "null",
+            _ => false,
+        }
+    }
+}
+
+/// 多节点故障转移：按健康状态和延迟排序依次尝试，传输错误、超时和 429 时换下一个节点
+///
+/// 配置了 quorum 时，`QUORUM_METHODS` 中的只读请求会并发发给所有节点，至少 quorum 个节点结果一致才返回；
+/// 查询 latest 的请求先固定到至少 quorum 个节点都已同步到的区块，避免节点间相差一两个区块导致误报
+///
+/// 广播交易切换节点后，下一个节点返回 already known（或 nonce too low 且能查到这笔交易）视为广播成功
+#[derive(Clone)]
+pub struct FailoverService {
+    endpoints: Arc<Vec<Endpoint>>,
+    quorum: Option<usize>,
+}
+
+impl FailoverService {
+    fn new(
+        endpoints: Vec<(String, BoxTransport)>,
+        quorum: Option<usize>,
+        timeout: Duration,
+    ) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, transport)| Endpoint {
+                url,
+                transport: TimeoutLayer::new(timeout).layer(transport),
+                stats: Mutex::new(EndpointStats {
+                    healthy: true,
+                    latency: None,
+                }),
+            })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            quorum,
+        }
+    }
+
+    /// 当前的节点尝试顺序
+    fn ranked(&self) -> Vec<&Endpoint> {
+        let stats: Vec<EndpointStats> = self.endpoints.iter().map(Endpoint::stats).collect();
+        rank(&stats)
+            .into_iter()
+            .map(|index| &self.endpoints[index])
+            .collect()
+    }
+
+    async fn request(&self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        if let (Some(quorum), RequestPacket::Single(single)) = (self.quorum, &request) {
+            let quorum_method = QUORUM_METHODS
+                .iter()
+                .find(|(method, _)| *method == single.method());
+            if let Some(&(method, block_index)) = quorum_method {
+                match block_param(single.params(), block_index) {
+                    BlockParam::Pinned => {
+                        return self.request_quorum(request, method, quorum).await;
+                    }
+                    BlockParam::Latest => {
+                        let block = self.quorum_block_number(quorum).await?;
+                        let pinned =
+                            with_block(single, block_index, block).map_err(RpcError::SerError)?;
+                        return self.request_quorum(pinned, method, quorum).await;
+                    }
+                    // pending 状态各节点的交易池不同，无法比较，按普通请求处理
+                    BlockParam::Unpinnable => {}
+                }
+            }
+        }
+
+        let mut last_error = None;
+        for endpoint in self.ranked() {
+            match endpoint.call(request.clone()).await {
+                Err(error) if should_fail_over(&error) => {
+                    tracing::debug!(
+                        "RPC {} failed, trying next endpoint: {}",
+                        endpoint.url,
+                        error
+                    );
+                    last_error = Some(error);
+                }
+                Ok(response) if last_error.is_some() => {
+                    return Ok(endpoint.already_broadcast(&request, response).await);
+                }
+                result => return result,
+            }
+        }
+        // 保留最后一个错误本身，全部节点都限流时外层的重试层仍会退避重试
+        Err(last_error
+            .unwrap_or_else(|| TransportErrorKind::custom_str("No RPC endpoints configured")))
+    }
+
+    async fn request_quorum(
+        &self,
+        request: RequestPacket,
+        method: &str,
+        quorum: usize,
+    ) -> Result<ResponsePacket, TransportError> {
+        let mut pending: FuturesUnordered<_> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.call(request.clone()))
+            .collect();
+
+        // 每个不同的结果及其票数，第一个达到 quorum 的结果返回
+        let mut tally: Vec<(Value, usize, ResponsePacket)> = Vec::new();
+        let mut last_error = None;
+        while let Some(result) = pending.next().await {
+            let response = match result {
+                Ok(response) => response,
+                Err(error) => {
+                    last_error = Some(error);
+                    continue;
+                }
+            };
+            let Some(value) = response_value(&response) else {
+                return Ok(response);
+            };
+            match tally.iter_mut().find(|(existing, _, _)| *existing == value) {
+                Some((_, votes, _)) => *votes += 1,
+                None => tally.push((value, 1, response)),
+            }
+            if let Some(index) = tally.iter().position(|(_, votes, _)| *votes >= quorum) {
+                return Ok(tally.swap_remove(index).2);
+            }
+        }
+
+        let best = tally.iter().map(|(_, votes, _)| *votes).max().unwrap_or(0);
+        let mut message = format!(
+            "RPC quorum not reached for {}: {} of {} endpoints agree, {} required",
+            method,
+            best,
+            self.endpoints.len(),
+            quorum
+        );
+        if tally.len() > 1 {
+            message.push_str(&format!(", {} different results", tally.len()));
+        }
+        if let Some(error) = last_error {
+            message.push_str(&format!(", last error: {}", error));
+        }
+        Err(TransportErrorKind::custom_str(&message))
+    }
+
+    /// 至少 quorum 个节点都已同步到的最高区块
+    async fn quorum_block_number(&self, quorum: usize) -> Result<u64, TransportError> {
+        let request = block_number_request().map_err(RpcError::SerError)?;
+        let responses = futures::future::join_all(
+            self.endpoints
+                .iter()
+                .map(|endpoint| endpoint.call(request.clone())),
+        )
+        .await;
+        let heights: Vec<u64> = responses
+            .iter()
+            .filter_map(|response| block_height(response.as_ref().ok()?))
+            .collect();
+        quorum_height(heights.clone(), quorum).ok_or_else(|| {
+            TransportErrorKind::custom_str(&format!(
+                "RPC quorum not reached for eth_blockNumber: {} of {} endpoints responded, {} required",
+                heights.len(),
+                self.endpoints.len(),
+                quorum
+            ))
+        })
+    }
+
+    /// 定期用 eth_blockNumber 探测每个节点，更新健康状态和延迟；服务被释放后任务自动退出
+    fn spawn_health_checks(&self, interval: Duration) {
+        let endpoints = Arc::downgrade(&self.endpoints);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(endpoints) = Weak::upgrade(&endpoints) else {
+                    break;
+                };
+                let checks = endpoints.iter().map(|endpoint| async move {
+                    match block_number_request() {
+                        Ok(request) => {
+                            if let Err(error) = endpoint.call(request).await {
+                                endpoint.track_failure(&error);
+                            }
+                        }
+                        Err(error) => tracing::warn!("Failed to build health check: {}", error),
+                    }
+                });
+                futures::future::join_all(checks).await;
+            }
+        });
+    }
+}
+
+impl Service<RequestPacket> for FailoverService {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move { this.request(request).await })
+    }
+}
+
+/// 只有节点自身的问题才切换：连接失败、超时、限流或无效响应；合约 revert 等执行错误直接返回
+fn should_fail_over(error: &TransportError) -> bool {
+    match error {
+        RpcError::Transport(_) | RpcError::NullResp | RpcError::DeserError { .. } => true,
+        RpcError::ErrorResp(payload) => payload.is_retry_err(),
+        _ => false,
+    }
+}
+
+/// 健康的节点在前，同等健康状态下延迟低的在前，尚未测得延迟的按配置顺序排在最后
+fn rank(stats: &[EndpointStats]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..stats.len()).collect();
+    order.sort_by_key(|&index| {
+        let stats = stats[index];
+        (!stats.healthy, stats.latency.unwrap_or(Duration::MAX))
+    });
+    order
+}
+
+fn ewma(previous: Option<Duration>, sample: Duration) -> Duration {
+    match previous {
+        Some(previous) => previous.mul_f64(0.7) + sample.mul_f64(0.3),
+        None => sample,
+    }
+}
+
+/// 用于比较的响应内容，批量请求返回 None
+fn response_value(response: &ResponsePacket) -> Option<Value> {
+    let ResponsePacket::Single(Response { payload, .. }) = response else {
+        return None;
+    };
+    Some(match payload {
+        ResponsePayload::Success(result) => serde_json::from_str(result.get())
+            .unwrap_or_else(|_| Value::String(result.get().to_string())),
+        ResponsePayload::Failure(error) => {
+            serde_json::json!({ "code": error.code, "message": error.message })
+        }
+    })
+}
+
+/// 请求中的区块参数
+#[derive(Debug, PartialEq, Eq)]
+enum BlockParam {
+    Pinned,     // 具体的区块号、哈希或 safe/finalized 等标签，可以直接比较各节点结果
+    Latest,     // latest 或省略，需要先固定到同一个区块
+    Unpinnable, // pending 或无法解析的参数
+}
+
+fn block_param(params: Option<&RawValue>, index: usize) -> BlockParam {
+    let Some(params) = params else {
+        return BlockParam::Latest;
+    };
+    let Ok(params) = serde_json::from_str::<Vec<Value>>(params.get()) else {
+        return BlockParam::Unpinnable;
+    };
+    match params.get(index) {
+        None | Some(Value::Null) => BlockParam::Latest,
+        Some(Value::String(tag)) if tag == "latest" => BlockParam::Latest,
+        Some(Value::String(tag)) if tag == "pending" => BlockParam::Unpinnable,
+        Some(_) => BlockParam::Pinned,
+    }
+}
+
+/// 把请求的区块参数替换为指定区块号，其余参数和请求 id 不变
+fn with_block(
+    request: &SerializedRequest,
+    index: usize,
+    block: u64,
+) -> serde_json::Result<RequestPacket> {
+    let mut params: Vec<Value> = match request.params() {
+        Some(params) => serde_json::from_str(params.get())?,
+        None => Vec::new(),
+    };
+    if params.len() <= index {
+        params.resize(index + 1, Value::Null);
+    }
+    params[index] = Value::String(format!("{:#x}", block));
+    Ok(RequestPacket::Single(
+        Request::new(request.method().to_string(), request.id().clone(), params).serialize()?,
+    ))
+}
+
+/// 各节点区块高度从高到低的第 quorum 个，即至少 quorum 个节点都已同步到的区块
+fn quorum_height(mut heights: Vec<u64>, quorum: usize) -> Option<u64> {
+    heights.sort_unstable_by(|a, b| b.cmp(a));
+    heights.get(quorum.checked_sub(1)?).copied()
+}
+
+fn block_height(response: &ResponsePacket) -> Option<u64> {
+    let ResponsePacket::Single(Response {
+        payload: ResponsePayload::Success(result),
+        ..
+    }) = response
+    else {
+        return None;
+    };
+    serde_json::from_str::<U64>(result.get())
+        .ok()
+        .map(|height| height.to::<u64>())
+}
+
+/// 广播交易的错误信息表明这笔交易已经在节点的交易池中，或者同一 nonce 已被使用
+fn is_already_broadcast_error(message: &str) -> (bool, bool) {
+    let message = message.to_lowercase();
+    let known = ["already known", "known transaction", "already imported"]
+        .iter()
+        .any(|pattern| message.contains(pattern));
+    (known, message.contains("nonce too low"))
+}
+
+/// eth_sendRawTransaction 请求中原始交易的哈希
+fn raw_transaction_hash(request: &RequestPacket) -> Option<String> {
+    let RequestPacket::Single(single) = request else {
+        return None;
+    };
+    if single.method() != SEND_RAW_TRANSACTION {
+        return None;
+    }
+    let (raw,): (Bytes,) = serde_json::from_str(single.params()?.get()).ok()?;
+    Some(keccak256(&raw).to_string())
+}
+
+fn block_number_request() -> serde_json::Result<RequestPacket> {
+    let params: [(); 0] = [];
+    Ok(RequestPacket::Single(
+        Request::new("eth_blockNumber", Id::Number(0), params).serialize()?,
+    ))
+}
+
+/// 建立带超时、故障转移和限流重试的只读 provider，支持 http(s)、ws(s) 和 IPC 地址
+///
+/// 每个节点有独立的超时；请求失败时依次切换到下一个节点，所有节点都遇到 429/503 时外层按退避策略整体重试
+pub async fn connect(
+    rpc_urls: &[String],
+    quorum: Option<usize>,
+    config: &RpcConfig,
+) -> Result<DynProvider> {
+    if rpc_urls.is_empty() {
+        bail!("No RPC URL configured");
+    }
+    let mut endpoints = Vec::with_capacity(rpc_urls.len());
+    let mut is_local = true;
+    for rpc_url in rpc_urls {
+        let connection: BuiltInConnectionString = rpc_url
+            .parse()
+            .with_context(|| format!("Invalid RPC URL {}", rpc_url))?;
+        is_local &= connection.is_local();
+        let transport = connection
+            .connect_boxed()
+            .await
+            .with_context(|| format!("Failed to connect to RPC {}", rpc_url))?;
+        endpoints.push((rpc_url.clone(), transport));
+    }
+
+    let failover =
+        FailoverService::new(endpoints, quorum, Duration::from_secs(config.timeout_secs));
+    if rpc_urls.len() > 1 && config.health_check_interval_secs > 0 {
+        failover.spawn_health_checks(Duration::from_secs(config.health_check_interval_secs));
+    }
+    let client: RpcClient = ClientBuilder::default()
+        .layer(RetryBackoffLayer::new(
+            config.max_retries,
+            config.initial_backoff_ms,
+            config.compute_units_per_second,
+        ))
+        .transport(failover, is_local);
+    Ok(RootProvider::new(client).erased())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::rpc::json_rpc::ErrorPayload;
+
+    fn stats(healthy: bool, latency_ms: Option<u64>) -> EndpointStats {
+        EndpointStats {
+            healthy,
+            latency: latency_ms.map(Duration::from_millis),
+        }
+    }
+
+    fn success(result: &str) -> ResponsePacket {
+        ResponsePacket::Single(Response {
+            id: Id::Number(1),
+            payload: ResponsePayload::Success(RawValue::from_string(result.to_string()).unwrap()),
+        })
+    }
+
+    #[test]
+    fn test_rank_prefers_healthy_and_fast() {
+        let order = rank(&[
+            stats(true, Some(300)),
+            stats(false, Some(10)),
+            stats(true, Some(50)),
+            stats(true, None),
+        ]);
+        assert_eq!(order, vec![2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn test_rank_keeps_config_order_without_measurements() {
+        let order = rank(&[stats(true, None), stats(true, None), stats(true, None)]);
+        assert_eq!(order, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_response_value_ignores_formatting() {
+        let a = response_value(&success(r#"{"a":"0x1","b":2}"#)).unwrap();
+        let b = response_value(&success(r#"{ "b": 2, "a": "0x1" }"#)).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, response_value(&success(r#"{"a":"0x2","b":2}"#)).unwrap());
+    }
+
+    fn request(method: &'static str, params: Value) -> SerializedRequest {
+        Request::new(method, Id::Number(7), params)
+            .serialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_block_param() {
+        let call = |params: Value| block_param(request("eth_call", params).params(), 1);
+        assert_eq!(call(serde_json::json!([{}])), BlockParam::Latest);
+        assert_eq!(call(serde_json::json!([{}, "latest"])), BlockParam::Latest);
+        assert_eq!(
+            call(serde_json::json!([{}, "pending"])),
+            BlockParam::Unpinnable
+        );
+        assert_eq!(call(serde_json::json!([{}, "0x10"])), BlockParam::Pinned);
+        assert_eq!(
+            call(serde_json::json!([{}, { "blockHash": "0x01" }])),
+            BlockParam::Pinned
+        );
+    }
+
+    #[test]
+    fn test_with_block_keeps_other_params() {
+        let original = request(
+            "eth_call",
+            serde_json::json!([{ "to": "0x01" }, "latest", {}]),
+        );
+        let RequestPacket::Single(pinned) = with_block(&original, 1, 255).unwrap() else {
+            panic!("expected single request");
+        };
+        assert_eq!(pinned.id(), &Id::Number(7));
+        let params: Value = serde_json::from_str(pinned.params().unwrap().get()).unwrap();
+        assert_eq!(params, serde_json::json!([{ "to": "0x01" }, "0xff", {}]));
+
+        let balance = request("eth_getBalance", serde_json::json!(["0x01"]));
+        let RequestPacket::Single(pinned) = with_block(&balance, 1, 16).unwrap() else {
+            panic!("expected single request");
+        };
+        let params: Value = serde_json::from_str(pinned.params().unwrap().get()).unwrap();
+        assert_eq!(params, serde_json::json!(["0x01", "0x10"]));
+    }
+
+    #[test]
+    fn test_quorum_height() {
+        assert_eq!(quorum_height(vec![100, 102, 101], 2), Some(101));
+        assert_eq!(quorum_height(vec![100, 102, 101], 3), Some(100));
+        assert_eq!(quorum_height(vec![100], 2), None);
+        assert_eq!(block_height(&success(r#""0x1b4""#)), Some(436));
+    }
+
+    #[test]
+    fn test_already_broadcast() {
+        assert_eq!(is_already_broadcast_error("already known"), (true, false));
+        assert_eq!(
+            is_already_broadcast_error("Known transaction: 0xabc"),
+            (true, false)
+        );
+        assert_eq!(
+            is_already_broadcast_error("nonce too low: next nonce 5, tx nonce 4"),
+            (false, true)
+        );
+        assert_eq!(
+            is_already_broadcast_error("insufficient funds for gas * price + value"),
+            (false, false)
+        );
+
+        let raw = "0x02f86b0180843b9aca00850c92a69c0082520894";
+        let send = RequestPacket::Single(request(SEND_RAW_TRANSACTION, serde_json::json!([raw])));
+        let expected = keccak256(alloy::primitives::hex::decode(raw).unwrap()).to_string();
+        assert_eq!(raw_transaction_hash(&send), Some(expected));
+        let call = RequestPacket::Single(request("eth_call", serde_json::json!([{}])));
+        assert_eq!(raw_transaction_hash(&call), None);
+    }
+
+    #[test]
+    fn test_fail_over_only_on_endpoint_errors() {
+        assert!(should_fail_over(&TransportErrorKind::custom_str(
+            "timed out"
+        )));
+        let rate_limited: ErrorPayload =
+            serde_json::from_str(r#"{"code":429,"message":"Too Many Requests"}"#).unwrap();
+        assert!(should_fail_over(&RpcError::ErrorResp(rate_limited)));
+        let reverted: ErrorPayload =
+            serde_json::from_str(r#"{"code":3,"message":"execution reverted"}"#).unwrap();
+        assert!(!should_fail_over(&RpcError::ErrorResp(reverted)));
+    }
+}
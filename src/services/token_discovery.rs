@@ -0,0 +1,378 @@
+use crate::config::DiscoveryConfig;
+use crate::models::IERC20;
+use alloy::primitives::Address;
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError};
+use tokio::sync::Mutex;
+
+/// 每次扫描都重新检查最近这些区块，避免链重组导致漏掉 Transfer
+const REORG_SAFETY_BLOCKS: u64 = 64;
+
+/// 单个钱包的扫描缓存
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryCache {
+    pub last_scanned_block: Option<u64>,
+    pub tokens: BTreeSet<Address>,
+}
+
+impl DiscoveryCache {
+    /// 扫描了 [start, latest] 后更新游标：只有扫描区间与已扫描的部分相连时才前移，
+    /// 否则 start 之前的区块还没扫过，游标保持不变，之后的默认扫描会补上
+    pub fn advance(&mut self, start: u64, latest: u64) {
+        let contiguous = match self.last_scanned_block {
+            Some(scanned) => start <= scanned.saturating_add(1),
+            None => start == 0,
+        };
+        if contiguous {
+            self.last_scanned_block = Some(
+                latest
+                    .saturating_sub(REORG_SAFETY_BLOCKS)
+                    .max(self.last_scanned_block.unwrap_or(0)),
+            );
+        }
+    }
+}
+
+/// 一次扫描的结果，tokens 包含缓存中已发现的代币
+#[derive(Debug, Clone)]
+pub struct Discovered {
+    pub tokens: Vec<Address>,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub requests: usize, // 本次发出的 eth_getLogs 请求数
+}
+
+/// eth_getLogs 的分段大小
+///
+/// 没有被拒绝时每次成功后翻倍；被拒绝后记住本次扫描中被拒绝的最小区间，之后只在它以下按剩余差距的一半增长，
+/// 避免对有固定区间上限的节点每隔一次请求就失败一次
+#[derive(Debug, Clone, Copy)]
+struct ChunkRange {
+    range: u64,
+    max: u64,
+    succeeded: u64,        // 本次扫描中成功过的最大区间
+    rejected: Option<u64>, // 本次扫描中被拒绝的最小区间
+}
+
+impl ChunkRange {
+    fn new(initial: u64, max: u64) -> Self {
+        let max = max.max(1);
+        Self {
+            range: initial.clamp(1, max),
+            max,
+            succeeded: 0,
+            rejected: None,
+        }
+    }
+
+    fn on_success(&mut self, size: u64) {
+        self.succeeded = self.succeeded.max(size);
+        let next = match self.rejected {
+            None => self.range.saturating_mul(2),
+            Some(rejected) => self.range + rejected.saturating_sub(self.range) / 2,
+        };
+        self.range = next.min(self.max);
+    }
+
+    /// 有成功过的更小区间时退回到该区间，否则减半
+    fn on_rejected(&mut self, size: u64) {
+        self.rejected = Some(self.rejected.map_or(size, |rejected| rejected.min(size)));
+        self.range = if self.succeeded > 0 && self.succeeded < size {
+            self.succeeded
+        } else {
+            (size / 2).max(1)
+        };
+    }
+}
+
+/// 扫描钱包转入转出的 ERC20 Transfer 日志来发现持有的代币，结果按链和钱包缓存在本地
+#[derive(Debug, Clone)]
+pub struct TokenDiscovery {
+    config: DiscoveryConfig,
+    // 同一链上的同一钱包同一时间只扫描一次，避免并发写同一缓存文件；不同钱包互不等待
+    locks: Arc<std::sync::Mutex<HashMap<(u64, Address), Arc<Mutex<()>>>>>,
+}
+
+impl Default for TokenDiscovery {
+    fn default() -> Self {
+        Self::new(DiscoveryConfig::default())
+    }
+}
+
+impl TokenDiscovery {
+    pub fn new(config: DiscoveryConfig) -> Self {
+        Self {
+            config,
+            locks: Arc::default(),
+        }
+    }
+
+    /// 取得 (链, 钱包) 的扫描锁，顺便清理没有扫描在使用的锁
+    fn wallet_lock(&self, chain_id: u64, wallet: Address) -> Arc<Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(PoisonError::into_inner);
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry((chain_id, wallet)).or_default().clone()
+    }
+
+    fn cache_path(&self, chain_id: u64, wallet: Address) -> PathBuf {
+        self.config
+            .cache_dir
+            .join(chain_id.to_string())
+            .join(format!("{}.json", wallet.to_string().to_lowercase()))
+    }
+
+    /// 读取缓存，文件不存在或已损坏时从头扫描
+    pub fn load(&self, chain_id: u64, wallet: Address) -> DiscoveryCache {
+        let path = self.cache_path(chain_id, wallet);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return DiscoveryCache::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid discovery cache {}: {}", path.display(), e);
+            DiscoveryCache::default()
+        })
+    }
+
+    fn store(&self, chain_id: u64, wallet: Address, cache: &DiscoveryCache) -> std::io::Result<()> {
+        let path = self.cache_path(chain_id, wallet);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // 先写临时文件再改名，中途退出不会留下半个文件
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(cache)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// 从上次扫描到的区块（或 from_block）继续扫描到最新区块，返回该钱包收发过的所有 ERC20 合约
+    pub async fn discover<P: Provider>(
+        &self,
+        provider: &P,
+        chain_id: u64,
+        wallet: Address,
+        from_block: Option<u64>,
+    ) -> Result<Discovered, McpError> {
+        let lock = self.wallet_lock(chain_id, wallet);
+        let _guard = lock.lock().await;
+        let mut cache = self.load(chain_id, wallet);
+        let latest = provider.get_block_number().await.map_err(|e| {
+            McpError::internal_error(format!("Failed to get latest block: {}", e), None)
+        })?;
+        let start = from_block
+            .or(cache.last_scanned_block.map(|block| block + 1))
+            .unwrap_or(0);
+
+        let mut requests = 0;
+        if start <= latest {
+            let transfer = Filter::new().event_signature(IERC20::Transfer::SIGNATURE_HASH);
+            let incoming = transfer.clone().topic2(wallet);
+            let outgoing = transfer.topic1(wallet);
+            for filter in [incoming, outgoing] {
+                let (logs, count) = self
+                    .get_logs_chunked(provider, &filter, start, latest)
+                    .await?;
+                requests += count;
+                cache.tokens.extend(erc20_contracts(&logs));
+            }
+        }
+        cache.advance(start, latest);
+        if let Err(e) = self.store(chain_id, wallet, &cache) {
+            tracing::warn!("Failed to write discovery cache: {}", e);
+        }
+
+        Ok(Discovered {
+            tokens: cache.tokens.into_iter().collect(),
+            from_block: start,
+            to_block: latest,
+            requests,
+        })
+    }
+
+    /// 分段调用 eth_getLogs：节点因区间或结果过大拒绝时缩小区间重试，成功后逐步放大，不超过 max_block_range 和本次扫描中被拒绝过的区间
+    async fn get_logs_chunked<P: Provider>(
+        &self,
+        provider: &P,
+        filter: &Filter,
+        from: u64,
+        to: u64,
+    ) -> Result<(Vec<Log>, usize), McpError> {
+        let mut logs = Vec::new();
+        let mut requests = 0;
+        let mut range = ChunkRange::new(self.config.block_range, self.config.max_block_range);
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start.saturating_add(range.range - 1));
+            requests += 1;
+            let chunk = filter.clone().from_block(start).to_block(end);
+            match provider.get_logs(&chunk).await {
+                Ok(chunk) => {
+                    logs.extend(chunk);
+                    if end == u64::MAX {
+                        break;
+                    }
+                    range.on_success(end - start + 1);
+                    start = end + 1;
+                }
+                Err(e) if end > start && is_range_limit_error(&e.to_string()) => {
+                    range.on_rejected(end - start + 1);
+                    tracing::debug!(
+                        "eth_getLogs {}..={} rejected, retrying with {} blocks: {}",
+                        start,
+                        end,
+                        range.range,
+                        e
+                    );
+                }
+                Err(e) => {
+                    return Err(McpError::internal_error(
+                        format!("eth_getLogs failed for blocks {}..={}: {}", start, end, e),
+                        None,
+                    ))
+                }
+            }
+        }
+        Ok((logs, requests))
+    }
+}
+
+/// ERC20 的 Transfer 有 3 个 topic，ERC721 的 tokenId 也是 indexed，有 4 个 topic
+fn erc20_contracts(logs: &[Log]) -> impl Iterator<Item = Address> + '_ {
+    logs.iter()
+        .filter(|log| log.topics().len() == 3)
+        .map(|log| log.address())
+}
+
+/// 各家节点对 eth_getLogs 区间或结果数量超限的报错
+fn is_range_limit_error(message: &str) -> bool {
+    const PATTERNS: &[&str] = &[
+        "block range",
+        "range is too large",
+        "range too large",
+        "too wide",
+        "is limited to",
+        "query returned more than",
+        "too many results",
+        "response size",
+        "limit exceeded",
+        "exceeds max",
+        "max results",
+        "query timeout",
+    ];
+    let message = message.to_lowercase();
+    PATTERNS.iter().any(|pattern| message.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    #[test]
+    fn test_range_limit_errors() {
+        assert!(is_range_limit_error(
+            "server returned an error response: error code -32005: query returned more than 10000 results"
+        ));
+        assert!(is_range_limit_error(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        ));
+        assert!(is_range_limit_error(
+            "eth_getLogs is limited to a 10,000 range"
+        ));
+        assert!(!is_range_limit_error("execution reverted"));
+        assert!(!is_range_limit_error("error sending request for url"));
+    }
+
+    /// 模拟区间上限固定的节点，返回 (请求数, 失败数)
+    fn scan_with_cap(cap: u64, to: u64) -> (u64, u64) {
+        let mut range = ChunkRange::new(10_000, 1_000_000);
+        let (mut start, mut requests, mut rejected) = (0, 0, 0);
+        while start <= to {
+            let end = to.min(start + range.range - 1);
+            requests += 1;
+            if end - start + 1 <= cap {
+                range.on_success(end - start + 1);
+                start = end + 1;
+            } else {
+                rejected += 1;
+                range.on_rejected(end - start + 1);
+            }
+        }
+        (requests, rejected)
+    }
+
+    #[test]
+    fn test_chunk_range_respects_fixed_cap() {
+        // 上限 2000 块时最少需要 500 次请求，只允许少量探测失败
+        let (requests, rejected) = scan_with_cap(2_000, 999_999);
+        assert!(rejected <= 10, "rejected {} times", rejected);
+        assert!(requests <= 520, "{} requests", requests);
+
+        let (requests, rejected) = scan_with_cap(10_000, 19_999_999);
+        assert!(rejected <= 20, "rejected {} times", rejected);
+        assert!(requests <= 2_050, "{} requests", requests);
+
+        // 没有上限时按倍增放大到 max_block_range
+        let (requests, rejected) = scan_with_cap(u64::MAX, 19_999_999);
+        assert_eq!(rejected, 0);
+        assert!(requests <= 30, "{} requests", requests);
+    }
+
+    #[test]
+    fn test_cursor_only_advances_over_contiguous_scans() {
+        // 首次扫描从中间开始时，之前的区块没有扫过，游标不动
+        let mut cache = DiscoveryCache::default();
+        cache.advance(1_000, 2_000);
+        assert_eq!(cache.last_scanned_block, None);
+        cache.advance(0, 2_000);
+        assert_eq!(cache.last_scanned_block, Some(2_000 - REORG_SAFETY_BLOCKS));
+
+        // from_block 跳过了游标之后的区块
+        cache.advance(5_000, 6_000);
+        assert_eq!(cache.last_scanned_block, Some(2_000 - REORG_SAFETY_BLOCKS));
+        // 从游标之前或紧接着游标开始的扫描可以前移
+        cache.advance(1_937, 3_000);
+        assert_eq!(cache.last_scanned_block, Some(3_000 - REORG_SAFETY_BLOCKS));
+        cache.advance(0, 2_500);
+        assert_eq!(cache.last_scanned_block, Some(3_000 - REORG_SAFETY_BLOCKS));
+    }
+
+    #[test]
+    fn test_wallet_locks_are_independent() {
+        let discovery = TokenDiscovery::default();
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        let first = discovery.wallet_lock(1, a);
+        let _guard = first.try_lock().unwrap();
+        // 其他钱包或其他链上的同一钱包不受影响
+        assert!(discovery.wallet_lock(1, b).try_lock().is_ok());
+        assert!(discovery.wallet_lock(10, a).try_lock().is_ok());
+        // 同一钱包共用一把锁
+        assert!(discovery.wallet_lock(1, a).try_lock().is_err());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("wallet-mcp-discovery-{}", std::process::id()));
+        let discovery = TokenDiscovery::new(DiscoveryConfig {
+            cache_dir: dir.clone(),
+            ..Default::default()
+        });
+        let wallet = address!("0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        assert_eq!(discovery.load(1, wallet), DiscoveryCache::default());
+
+        let cache = DiscoveryCache {
+            last_scanned_block: Some(100),
+            tokens: BTreeSet::from([address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")]),
+        };
+        discovery.store(1, wallet, &cache).unwrap();
+        assert_eq!(discovery.load(1, wallet), cache);
+        assert_eq!(discovery.load(10, wallet), DiscoveryCache::default());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
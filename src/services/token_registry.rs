@@ -0,0 +1,177 @@
+use alloy::primitives::Address;
+use anyhow::{Context, Result};
+use rmcp::ErrorData as McpError;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Uniswap token list（https://tokenlists.org）格式，只读取需要的字段
+#[derive(Debug, Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub chain_id: u64,
+    pub address: Address,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// 从 token list 加载的代币表，按链把符号或名称解析为地址
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    tokens: Vec<TokenInfo>,
+}
+
+impl TokenRegistry {
+    /// 依次加载多个 token list，同一链上相同地址只保留第一次出现的条目
+    pub fn load(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        let mut registry = Self::default();
+        for path in paths {
+            let path = path.as_ref();
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read token list {}", path.display()))?;
+            registry
+                .extend_from_json(&content)
+                .with_context(|| format!("Invalid token list {}", path.display()))?;
+        }
+        Ok(registry)
+    }
+
+    pub fn extend_from_json(&mut self, content: &str) -> Result<()> {
+        let list: TokenList = serde_json::from_str(content)?;
+        for token in list.tokens {
+            if !self
+                .tokens
+                .iter()
+                .any(|t| t.chain_id == token.chain_id && t.address == token.address)
+            {
+                self.tokens.push(token);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// 该链上的地址是否在 token list 中
+    pub fn contains(&self, chain_id: u64, address: Address) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| t.chain_id == chain_id && t.address == address)
+    }
+
+    /// 按符号查找，其次按名称查找；大小写完全一致的匹配优先，多个候选时报错并列出候选
+    pub fn resolve(&self, chain_id: u64, token: &str) -> Result<Option<&TokenInfo>, McpError> {
+        let on_chain: Vec<&TokenInfo> = self
+            .tokens
+            .iter()
+            .filter(|t| t.chain_id == chain_id)
+            .collect();
+        let fields: [fn(&TokenInfo) -> &str; 2] = [|t| t.symbol.as_str(), |t| t.name.as_str()];
+        for field in fields {
+            let exact: Vec<&TokenInfo> = on_chain
+                .iter()
+                .copied()
+                .filter(|t| field(t) == token)
+                .collect();
+            let candidates = if exact.is_empty() {
+                on_chain
+                    .iter()
+                    .copied()
+                    .filter(|t| field(t).eq_ignore_ascii_case(token))
+                    .collect()
+            } else {
+                exact
+            };
+            match candidates.as_slice() {
+                [] => continue,
+                [only] => return Ok(Some(*only)),
+                _ => {
+                    let candidates: Vec<String> = candidates
+                        .iter()
+                        .map(|t| format!("{} ({}) {}", t.symbol, t.name, t.address))
+                        .collect();
+                    return Err(McpError::invalid_params(
+                        format!(
+                            "Token '{}' is ambiguous on chain {}, use an address: {}",
+                            token,
+                            chain_id,
+                            candidates.join(", ")
+                        ),
+                        None,
+                    ));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    const LIST: &str = r#"{
+        "name": "Test List",
+        "timestamp": "2024-01-01T00:00:00Z",
+        "version": { "major": 1, "minor": 0, "patch": 0 },
+        "tokens": [
+            { "chainId": 1, "address": "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984", "name": "Uniswap", "symbol": "UNI", "decimals": 18, "logoURI": "ipfs://x" },
+            { "chainId": 1, "address": "0x0000000000000000000000000000000000000001", "name": "Fake USD A", "symbol": "FUSD", "decimals": 6 },
+            { "chainId": 1, "address": "0x0000000000000000000000000000000000000002", "name": "Fake USD B", "symbol": "FUSD", "decimals": 6 },
+            { "chainId": 10, "address": "0x6fd9d7AD17242c41f7131d257212c54A0e816691", "name": "Uniswap", "symbol": "UNI", "decimals": 18 }
+        ]
+    }"#;
+
+    fn registry() -> TokenRegistry {
+        let mut registry = TokenRegistry::default();
+        registry.extend_from_json(LIST).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_resolve_symbol_and_name_per_chain() {
+        let registry = registry();
+        let uni = address!("0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984");
+        assert_eq!(registry.resolve(1, "UNI").unwrap().unwrap().address, uni);
+        assert!(registry.contains(1, uni));
+        assert!(!registry.contains(10, uni));
+        assert_eq!(registry.resolve(1, "uni").unwrap().unwrap().address, uni);
+        assert_eq!(
+            registry.resolve(1, "Uniswap").unwrap().unwrap().address,
+            uni
+        );
+        assert_eq!(
+            registry.resolve(10, "UNI").unwrap().unwrap().address,
+            address!("0x6fd9d7AD17242c41f7131d257212c54A0e816691")
+        );
+        assert!(registry.resolve(8453, "UNI").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ambiguous_symbol_lists_candidates() {
+        let error = registry().resolve(1, "FUSD").unwrap_err();
+        assert!(error.message.contains("Fake USD A"));
+        assert!(error.message.contains("Fake USD B"));
+        // 名称唯一时仍可解析
+        assert!(registry().resolve(1, "Fake USD B").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_duplicate_entries_are_merged() {
+        let mut registry = registry();
+        registry.extend_from_json(LIST).unwrap();
+        assert_eq!(registry.len(), 4);
+    }
+}
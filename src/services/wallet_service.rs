@@ -1,22 +1,143 @@
-use crate::handlers::{BalanceHandler, PriceHandler, SwapHandler};
-use crate::models::{GetBalanceRequest, GetTokenPriceRequest, SwapTokensRequest};
+use crate::config::{Config, PolicyConfig, RpcConfig};
+use crate::handlers::{
+    BalanceHandler, PortfolioHandler, PriceHandler, SwapHandler, TransactionHandler,
+    TransferHandler,
+};
+use crate::models::{
+    chain_by_name, DiscoverTokensRequest, GetBalanceRequest, GetBalancesRequest,
+    GetPortfolioRequest, GetPriceConsensusRequest, GetTokenPriceRequest, GetTransactionRequest,
+    SwapTokensRequest, TransferRequest, MAINNET,
+};
+use crate::services::token_discovery::TokenDiscovery;
+use crate::services::token_registry::TokenRegistry;
+use crate::services::ChainContext;
+use anyhow::{bail, Result};
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::{model::CallToolResult, ErrorData as McpError};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 /// 钱包服务，封装所有钱包相关的业务逻辑
+///
+/// provider 在启动时为每条链建立一次，克隆服务（例如 HTTP 传输下的每个会话）只复制 Arc
 #[derive(Clone)]
 pub struct WalletService {
-    private_key: String,
-    rpc_url: String,
+    private_key: Option<String>, // 未配置账户时只能使用查询工具
+    chains: Arc<HashMap<u64, Arc<ChainContext>>>, // 链 ID -> 该链的地址表和 provider
+    default_chain: u64,
+    policy: PolicyConfig,
+    discovery: TokenDiscovery, // 代币发现的本地缓存，所有会话共用
 }
 
 impl WalletService {
-    /// 创建新的钱包服务实例
-    pub fn new(private_key: String, rpc_url: String) -> Self {
-        Self {
-            private_key,
-            rpc_url,
+    /// 创建只连接以太坊主网的钱包服务实例
+    pub async fn new(private_key: String, rpc_url: String) -> Result<Self> {
+        let chain = ChainContext::connect(
+            MAINNET.name.to_string(),
+            vec![rpc_url],
+            None,
+            MAINNET,
+            &RpcConfig::default(),
+        )
+        .await?;
+        Ok(Self {
+            private_key: Some(private_key),
+            default_chain: chain.chain_id(),
+            chains: Arc::new(HashMap::from([(chain.chain_id(), Arc::new(chain))])),
+            policy: PolicyConfig::default(),
+            discovery: TokenDiscovery::default(),
+        })
+    }
+
+    /// 为配置中的每个网络建立一个共享 provider，默认网络作为未指定 chain 时的目标链
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        let tokens = Arc::new(TokenRegistry::load(&config.tokens.lists)?);
+        if !tokens.is_empty() {
+            tracing::info!("Loaded {} tokens from token lists", tokens.len());
+        }
+        let mut contexts = Vec::new();
+        for (name, network) in &config.networks {
+            let chain = ChainContext::from_network(name, network, &config.rpc)
+                .await?
+                .with_tokens(tokens.clone());
+            contexts.push(chain);
         }
+        // 没有 ENS 的链（L2）通过主网解析 ENS 名称
+        let mainnet_ens = contexts
+            .iter()
+            .find(|chain| chain.chain_id() == MAINNET.chain_id)
+            .and_then(|chain| chain.ens.clone());
+        let mut chains = HashMap::new();
+        for chain in contexts {
+            let chain = match (&chain.ens, &mainnet_ens) {
+                (None, Some(ens)) => chain.with_ens(ens.clone()),
+                _ => chain,
+            };
+            let name = chain.name.clone();
+            if let Some(existing) = chains.insert(chain.chain_id(), Arc::new(chain)) {
+                bail!(
+                    "Networks '{}' and '{}' both use chain id {}",
+                    existing.name,
+                    name,
+                    existing.chain_id()
+                );
+            }
+        }
+        let default_name = config.default_network_name();
+        let Some(default_chain) = chains
+            .values()
+            .find(|chain| chain.name == default_name)
+            .map(|chain| chain.chain_id())
+        else {
+            bail!("Default network '{}' is not configured", default_name);
+        };
+        Ok(Self {
+            private_key: config.signing_key()?,
+            chains: Arc::new(chains),
+            default_chain,
+            policy: config.policy.clone(),
+            discovery: TokenDiscovery::new(config.discovery.clone()),
+        })
+    }
+
+    /// 设置交易策略
+    pub fn with_policy(mut self, policy: PolicyConfig) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 按链 ID 或网络名查找已配置的链，None 表示默认网络
+    pub fn chain(&self, chain: Option<&str>) -> Result<&Arc<ChainContext>, McpError> {
+        let Some(chain) = chain.map(str::trim) else {
+            return Ok(&self.chains[&self.default_chain]);
+        };
+        let chain_id = match chain.parse::<u64>() {
+            Ok(chain_id) => Some(chain_id),
+            Err(_) => self
+                .chains
+                .values()
+                .find(|context| context.name.eq_ignore_ascii_case(chain))
+                .map(|context| context.chain_id())
+                .or_else(|| chain_by_name(chain).map(|addresses| addresses.chain_id)),
+        };
+        chain_id
+            .and_then(|chain_id| self.chains.get(&chain_id))
+            .ok_or_else(|| {
+                let mut available: Vec<String> = self
+                    .chains
+                    .values()
+                    .map(|context| format!("{} ({})", context.name, context.chain_id()))
+                    .collect();
+                available.sort();
+                McpError::invalid_params(
+                    format!(
+                        "Chain '{}' is not configured, available: {}",
+                        chain,
+                        available.join(", ")
+                    ),
+                    None,
+                )
+            })
     }
 
     /// 处理余额查询请求
@@ -24,36 +145,119 @@ impl WalletService {
         &self,
         request: Parameters<GetBalanceRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let handler = BalanceHandler::new(self.rpc_url.clone());
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = BalanceHandler::new(chain.clone());
         handler.handle_get_balance(request).await
     }
 
+    /// 处理批量余额查询请求
+    pub async fn get_balances(
+        &self,
+        request: Parameters<GetBalancesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = BalanceHandler::new(chain.clone()).with_discovery(self.discovery.clone());
+        handler.handle_get_balances(request).await
+    }
+
+    /// 处理代币发现请求
+    pub async fn discover_tokens(
+        &self,
+        request: Parameters<DiscoverTokensRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = BalanceHandler::new(chain.clone()).with_discovery(self.discovery.clone());
+        handler.handle_discover_tokens(request).await
+    }
+
+    /// 处理持仓估值请求
+    pub async fn get_portfolio(
+        &self,
+        request: Parameters<GetPortfolioRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = PortfolioHandler::new(chain.clone()).with_discovery(self.discovery.clone());
+        handler.handle_get_portfolio(request).await
+    }
+
     /// 处理代币价格查询请求
     pub async fn get_token_price(
         &self,
         request: Parameters<GetTokenPriceRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let handler = PriceHandler::new(self.rpc_url.clone());
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = PriceHandler::new(chain.clone());
         handler.handle_get_token_price(request).await
     }
 
+    /// 处理多来源价格共识检查请求
+    pub async fn get_price_consensus(
+        &self,
+        request: Parameters<GetPriceConsensusRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = PriceHandler::new(chain.clone());
+        handler.handle_get_price_consensus(request).await
+    }
+
     /// 处理代币交换请求
     pub async fn swap_tokens(
         &self,
         request: Parameters<SwapTokensRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let handler = SwapHandler::new(self.private_key.clone(), self.rpc_url.clone());
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = SwapHandler::new(self.signing_key("swap_tokens")?, chain.clone())
+            .with_policy(self.policy.clone());
         handler.handle_swap_tokens(request).await
     }
 
-    /// 获取 RPC URL（用于测试或其他需要）
+    /// 处理转账请求
+    pub async fn transfer(
+        &self,
+        request: Parameters<TransferRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = TransferHandler::new(self.signing_key("transfer")?, chain.clone())
+            .with_policy(self.policy.clone());
+        handler.handle_transfer(request).await
+    }
+
+    /// 处理交易状态查询请求
+    pub async fn get_transaction(
+        &self,
+        request: Parameters<GetTransactionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let chain = self.chain(request.0.chain.as_deref())?;
+        let handler = TransactionHandler::new(chain.clone());
+        handler.handle_get_transaction(request).await
+    }
+
+    /// 获取默认网络的 RPC URL（用于测试或其他需要）
     pub fn rpc_url(&self) -> &str {
-        &self.rpc_url
+        self.chains[&self.default_chain].rpc_url()
     }
 
-    /// 获取私钥（谨慎使用，仅在必要时）
-    pub fn private_key(&self) -> &str {
-        &self.private_key
+    /// 获取交易策略
+    pub fn policy(&self) -> &PolicyConfig {
+        &self.policy
+    }
+
+    /// 获取私钥（谨慎使用，仅在必要时），未配置账户时为 None
+    pub fn private_key(&self) -> Option<&str> {
+        self.private_key.as_deref()
+    }
+
+    /// 需要签名的工具取私钥，未配置账户时返回错误
+    fn signing_key(&self, tool: &str) -> Result<String, McpError> {
+        self.private_key.clone().ok_or_else(|| {
+            McpError::invalid_params(
+                format!(
+                    "{} requires an account, set PRIVATE_KEY or add [accounts] to the config file",
+                    tool
+                ),
+                None,
+            )
+        })
     }
 }
 
@@ -61,26 +265,39 @@ impl WalletService {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_wallet_service_creation() {
+    #[tokio::test]
+    async fn test_wallet_service_creation() {
         let service = WalletService::new(
             "test_private_key".to_string(),
             "https://test.rpc.url".to_string(),
-        );
-        
+        )
+        .await
+        .unwrap();
+
         assert_eq!(service.rpc_url(), "https://test.rpc.url");
-        assert_eq!(service.private_key(), "test_private_key");
+        assert_eq!(service.private_key(), Some("test_private_key"));
     }
 
-    #[test]
-    fn test_wallet_service_clone() {
-        let service = WalletService::new(
-            "test_key".to_string(),
-            "https://test.url".to_string(),
-        );
-        
+    #[tokio::test]
+    async fn test_wallet_service_clone() {
+        let service = WalletService::new("test_key".to_string(), "https://test.url".to_string())
+            .await
+            .unwrap();
+
         let cloned_service = service.clone();
         assert_eq!(service.rpc_url(), cloned_service.rpc_url());
         assert_eq!(service.private_key(), cloned_service.private_key());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_chain_lookup() {
+        let service = WalletService::new("test_key".to_string(), "https://test.url".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(service.chain(None).unwrap().chain_id(), 1);
+        assert_eq!(service.chain(Some("1")).unwrap().chain_id(), 1);
+        assert_eq!(service.chain(Some("Ethereum")).unwrap().chain_id(), 1);
+        assert!(service.chain(Some("base")).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use crate::auth::{require_auth, AuthConfig, Authenticator};
+use crate::server::McpServer;
+use anyhow::{anyhow, bail, Result};
+use axum::{middleware, routing::get, Router};
+use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use rmcp::transport::stdio;
+use rmcp::transport::streamable_http_server::{
+    session::local::LocalSessionManager, StreamableHttpService,
+};
+use rmcp::ServiceExt;
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// 默认监听地址和端口
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+pub const DEFAULT_PORT: u16 = 8000;
+
+/// 服务端使用的传输方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Stdio,
+    Http, // Streamable HTTP，挂载在 /mcp
+    Sse,  // SSE，GET /sse 建立连接，POST /message 发送消息
+}
+
+impl FromStr for Transport {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "stdio" => Ok(Transport::Stdio),
+            "http" => Ok(Transport::Http),
+            "sse" => Ok(Transport::Sse),
+            other => bail!("Unknown transport '{}', expected stdio, http or sse", other),
+        }
+    }
+}
+
+impl Display for Transport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Transport::Stdio => write!(f, "stdio"),
+            Transport::Http => write!(f, "http"),
+            Transport::Sse => write!(f, "sse"),
+        }
+    }
+}
+
+/// 命令行参数：--transport stdio|http|sse --bind <addr> --port <port> --config <path>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeOptions {
+    pub transport: Transport,
+    pub bind: SocketAddr,
+    pub config: Option<PathBuf>,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            transport: Transport::Stdio,
+            bind: SocketAddr::new(DEFAULT_BIND_ADDRESS, DEFAULT_PORT),
+            config: None,
+        }
+    }
+}
+
+impl ServeOptions {
+    /// 解析命令行参数（不含程序名），支持 `--flag value` 和 `--flag=value`
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow!("Missing value for {}", flag))
+            };
+            match flag.as_str() {
+                "--transport" => options.transport = value()?.parse()?,
+                "--bind" => {
+                    let value = value()?;
+                    options.bind.set_ip(
+                        value
+                            .parse()
+                            .map_err(|e| anyhow!("Invalid bind address '{}': {}", value, e))?,
+                    );
+                }
+                "--port" => {
+                    let value = value()?;
+                    options.bind.set_port(
+                        value
+                            .parse()
+                            .map_err(|e| anyhow!("Invalid port '{}': {}", value, e))?,
+                    );
+                }
+                "--config" => options.config = Some(PathBuf::from(value()?)),
+                other => bail!("Unknown argument '{}'", other),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// 按传输方式启动服务，HTTP/SSE 模式下收到 SIGTERM 或 Ctrl-C 时优雅退出
+pub async fn serve(
+    server: McpServer,
+    options: &ServeOptions,
+    auth: Option<AuthConfig>,
+) -> Result<()> {
+    if options.transport == Transport::Stdio {
+        return serve_stdio(server).await;
+    }
+
+    // 持有私钥的服务不允许在未配置认证的情况下暴露到网络上
+    let auth = match auth {
+        Some(auth) => Some(Authenticator::new(auth)),
+        None if options.bind.ip().is_loopback() => {
+            tracing::warn!("No authentication configured, only listening on loopback");
+            None
+        }
+        None => bail!(
+            "Refusing to listen on {} without authentication, configure [auth] or set MCP_AUTH_TOKENS",
+            options.bind
+        ),
+    };
+    match options.transport {
+        Transport::Http => serve_http(server, options.bind, auth).await,
+        Transport::Sse => serve_sse(server, options.bind, auth).await,
+        Transport::Stdio => unreachable!(),
+    }
+}
+
+/// 为 MCP 路由加上认证层，/healthz 不需要认证
+fn with_auth(router: Router, auth: Option<Authenticator>) -> Router {
+    let router = match auth {
+        Some(auth) => router.layer(middleware::from_fn_with_state(auth, require_auth)),
+        None => router,
+    };
+    router.route("/healthz", get(healthz))
+}
+
+async fn serve_stdio(server: McpServer) -> Result<()> {
+    let service = server.serve(stdio()).await.inspect_err(|e| {
+        tracing::error!("serving error: {:?}", e);
+    })?;
+    service.waiting().await?;
+    Ok(())
+}
+
+/// 多个客户端共享同一个 McpServer，每个会话克隆一份
+async fn serve_http(
+    server: McpServer,
+    bind: SocketAddr,
+    auth: Option<Authenticator>,
+) -> Result<()> {
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let router = with_auth(Router::new().nest_service("/mcp", service), auth);
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    tracing::info!("Streamable HTTP server listening on http://{}/mcp", bind);
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    tracing::info!("Streamable HTTP server stopped");
+    Ok(())
+}
+
+async fn serve_sse(server: McpServer, bind: SocketAddr, auth: Option<Authenticator>) -> Result<()> {
+    let config = SseServerConfig {
+        bind,
+        sse_path: "/sse".to_string(),
+        post_path: "/message".to_string(),
+        ct: Default::default(),
+        sse_keep_alive: None,
+    };
+    let (sse_server, router) = SseServer::new(config);
+    let router = with_auth(router, auth);
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    tracing::info!("SSE server listening on http://{}/sse", bind);
+    let ct = sse_server.with_service(move || server.clone());
+    let http_ct = ct.child_token();
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            tokio::select! {
+                _ = shutdown_signal() => {}
+                _ = http_ct.cancelled() => {}
+            }
+        })
+        .await?;
+    // 通知所有 SSE 会话退出
+    ct.cancel();
+    tracing::info!("SSE server stopped");
+    Ok(())
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// 等待 Ctrl-C 或 SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<ServeOptions> {
+        ServeOptions::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options, ServeOptions::default());
+        assert_eq!(options.transport, Transport::Stdio);
+    }
+
+    #[test]
+    fn test_parse_http_options() {
+        let options = parse(&["--transport", "http", "--bind=0.0.0.0", "--port", "9000"]).unwrap();
+        assert_eq!(options.transport, Transport::Http);
+        assert_eq!(options.bind, "0.0.0.0:9000".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_invalid_options() {
+        assert!(parse(&["--transport", "websocket"]).is_err());
+        assert!(parse(&["--port"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
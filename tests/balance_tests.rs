@@ -1,16 +1,18 @@
 use rmcp::handler::server::wrapper::Parameters;
 use tokio;
-use wallet_mcp::GetBalanceRequest;
+use wallet_mcp::{DiscoverTokensRequest, GetBalanceRequest, GetBalancesRequest};
 
 mod common;
 use common::*;
 
 #[tokio::test]
 async fn test_get_eth_balance_success() {
-    let server = create_test_server();
+    let server = create_test_server().await;
     let request = GetBalanceRequest {
         wallet_address: TEST_WALLET_ADDRESS.to_string(),
         token_address: None, // 查询ETH余额
+        block: None,
+        chain: None,
     };
 
     let result = server.get_balance(Parameters(request)).await;
@@ -34,22 +36,28 @@ async fn test_get_eth_balance_success() {
 
 #[tokio::test]
 async fn test_multiple_balance_queries() {
-    let server = create_test_server();
+    let server = create_test_server().await;
 
     // 并发查询多个余额
     let eth_request = GetBalanceRequest {
         wallet_address: TEST_WALLET_ADDRESS.to_string(),
         token_address: None,
+        block: None,
+        chain: None,
     };
 
     let usdc_request = GetBalanceRequest {
         wallet_address: TEST_WALLET_ADDRESS.to_string(),
         token_address: Some(USDC_ADDRESS.to_string()),
+        block: None,
+        chain: None,
     };
 
     let wbtc_request = GetBalanceRequest {
         wallet_address: TEST_WALLET_ADDRESS.to_string(),
         token_address: Some(WBTC_ADDRESS.to_string()),
+        block: None,
+        chain: None,
     };
 
     let (eth_result, usdc_result, wbtc_result) = tokio::join!(
@@ -62,3 +70,197 @@ async fn test_multiple_balance_queries() {
     assert!(usdc_result.is_ok(), "USDC余额查询应该成功");
     assert!(wbtc_result.is_ok(), "WBTC余额查询应该成功");
 }
+
+#[tokio::test]
+async fn test_get_balance_on_explicit_chain() {
+    let server = create_test_server().await;
+    let request = GetBalanceRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_address: None,
+        block: None,
+        chain: Some("1".to_string()),
+    };
+
+    let result = server.get_balance(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 指定链余额查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 指定链余额查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "按链 ID 指定默认网络应该成功");
+}
+
+#[tokio::test]
+async fn test_get_balance_unknown_chain() {
+    let server = create_test_server().await;
+    let request = GetBalanceRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_address: None,
+        block: None,
+        chain: Some("base".to_string()),
+    };
+
+    let result = server.get_balance(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => println!("❌ 未配置的链不应该返回结果: {:?}", response),
+        Err(error) => println!("✅ 未配置的链被拒绝: {:?}", error),
+    }
+
+    assert!(result.is_err(), "未配置的链应该返回错误");
+}
+
+#[tokio::test]
+async fn test_get_balance_at_block() {
+    let server = create_test_server().await;
+    let request = GetBalanceRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_address: Some(USDC_ADDRESS.to_string()),
+        block: Some("latest".to_string()),
+        chain: None,
+    };
+
+    let result = server.get_balance(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 指定区块的余额查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 指定区块的余额查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "指定区块的余额查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_balance_invalid_block() {
+    let server = create_test_server().await;
+    let request = GetBalanceRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_address: None,
+        block: Some("end of month".to_string()),
+        chain: None,
+    };
+
+    let result = server.get_balance(Parameters(request)).await;
+    assert!(result.is_err(), "无法解析的 block 应该返回错误");
+}
+
+#[tokio::test]
+async fn test_get_balance_ens_name() {
+    let server = create_test_server().await;
+    let request = GetBalanceRequest {
+        wallet_address: "vitalik.eth".to_string(),
+        token_address: None,
+        block: None,
+        chain: None,
+    };
+
+    let result = server.get_balance(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ ENS 名称余额查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ ENS 名称余额查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "ENS 名称应该解析为地址");
+}
+
+#[tokio::test]
+async fn test_get_balances_batch() {
+    let server = create_test_server().await;
+    let request = GetBalancesRequest {
+        wallet_addresses: vec![
+            TEST_WALLET_ADDRESS.to_string(),
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+        ],
+        token_addresses: Some(vec![
+            USDC_ADDRESS.to_string(),
+            WBTC_ADDRESS.to_string(),
+            DAI_ADDRESS.to_string(),
+            // 不是合约的地址，对应的单元格应返回错误而不是让整个请求失败
+            "0x0000000000000000000000000000000000000001".to_string(),
+        ]),
+        include_native: None,
+        discover: None,
+        chain: None,
+    };
+
+    let result = server.get_balances(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 批量余额查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 批量余额查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "批量余额查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_balances_invalid_wallet() {
+    let server = create_test_server().await;
+    let request = GetBalancesRequest {
+        wallet_addresses: vec!["invalid_address".to_string()],
+        token_addresses: None,
+        include_native: None,
+        discover: None,
+        chain: None,
+    };
+
+    let result = server.get_balances(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => println!("❌ 无效地址不应该返回结果: {:?}", response),
+        Err(error) => println!("✅ 无效地址被拒绝: {:?}", error),
+    }
+
+    assert!(result.is_err(), "无效钱包地址应该返回错误");
+}
+
+#[tokio::test]
+async fn test_discover_tokens() {
+    let server = create_test_server().await;
+    let request = DiscoverTokensRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        from_block: None,
+        chain: None,
+    };
+
+    let result = server.discover_tokens(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 代币发现成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 代币发现失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "代币发现应该成功");
+}
@@ -7,6 +7,7 @@ pub const TEST_PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cb
 pub const WETH_ADDRESS: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
 pub const USDC_ADDRESS: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
 pub const WBTC_ADDRESS: &str = "0x2260fac5e5542a773aa44fbcfedf7c193bc2c599";
+pub const DAI_ADDRESS: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
 
 /// 获取测试用的RPC URL
 pub fn get_test_rpc_url() -> String {
@@ -14,7 +15,31 @@ pub fn get_test_rpc_url() -> String {
 }
 
 /// 创建测试服务器实例
-pub fn create_test_server() -> McpServer {
+pub async fn create_test_server() -> McpServer {
     let rpc_url = get_test_rpc_url();
-    McpServer::new(TEST_PRIVATE_KEY.to_string(), rpc_url)
-}
\ No newline at end of file
+    McpServer::new(TEST_PRIVATE_KEY.to_string(), rpc_url).await.expect("Failed to create test server")
+}
+/// 会广播交易的测试只在本地 fork（如 `anvil --fork-url ...`）上运行，需设置 WALLET_MCP_FORK_TESTS=1
+#[allow(dead_code)]
+pub fn fork_tests_enabled() -> bool {
+    env::var("WALLET_MCP_FORK_TESTS").map(|value| value == "1").unwrap_or(false)
+}
+
+/// 取出工具响应中的文本
+#[allow(dead_code)]
+pub fn response_text(response: &rmcp::model::CallToolResult) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|content| content.as_text())
+        .map(|text| text.text.clone())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 取出响应文本中 `field: <value>` 的值
+#[allow(dead_code)]
+pub fn response_field<'a>(text: &'a str, field: &str) -> Option<&'a str> {
+    text.split(" | ")
+        .find_map(|part| part.trim().strip_prefix(field)?.strip_prefix(": "))
+}
@@ -0,0 +1,83 @@
+use rmcp::handler::server::wrapper::Parameters;
+use tokio;
+use wallet_mcp::GetPortfolioRequest;
+
+mod common;
+use common::*;
+
+#[tokio::test]
+async fn test_get_portfolio_default_tokens() {
+    let server = create_test_server().await;
+    let request = GetPortfolioRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_addresses: None, // 使用内置常用代币
+        min_value_usd: None,
+        discover: None,
+        chain: None,
+    };
+
+    let result = server.get_portfolio(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 持仓估值成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 持仓估值失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "持仓估值应该成功");
+    let response = result.unwrap();
+    assert!(!response.content.is_empty(), "响应内容不应为空");
+}
+
+#[tokio::test]
+async fn test_get_portfolio_with_token_list() {
+    let server = create_test_server().await;
+    let request = GetPortfolioRequest {
+        wallet_address: TEST_WALLET_ADDRESS.to_string(),
+        token_addresses: Some(vec![USDC_ADDRESS.to_string(), WETH_ADDRESS.to_string()]),
+        min_value_usd: Some(0.0), // 不过滤粉尘
+        discover: None,
+        chain: None,
+    };
+
+    let result = server.get_portfolio(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 指定代币持仓估值成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 指定代币持仓估值失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "指定代币的持仓估值应该成功");
+}
+
+#[tokio::test]
+async fn test_get_portfolio_invalid_wallet() {
+    let server = create_test_server().await;
+    let request = GetPortfolioRequest {
+        wallet_address: "invalid_address".to_string(),
+        token_addresses: None,
+        min_value_usd: None,
+        discover: None,
+        chain: None,
+    };
+
+    let result = server.get_portfolio(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => println!("❌ 无效地址不应该返回结果: {:?}", response),
+        Err(error) => println!("✅ 无效地址被拒绝: {:?}", error),
+    }
+
+    assert!(result.is_err(), "无效钱包地址应该返回错误");
+}
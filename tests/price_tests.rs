@@ -1,5 +1,5 @@
 use wallet_mcp::server::McpServer;
-use wallet_mcp::GetTokenPriceRequest;
+use wallet_mcp::{GetPriceConsensusRequest, GetTokenPriceRequest, PriceSource};
 use rmcp::handler::server::wrapper::Parameters;
 use std::env;
 use tokio;
@@ -9,10 +9,15 @@ use common::*;
 
 #[tokio::test]
 async fn test_get_token_price_success() {
-    let server = create_test_server();
+    let server = create_test_server().await;
     let request = GetTokenPriceRequest {
         token: WBTC_ADDRESS.to_string(),
         fee: Some(3000),
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
     };
 
     let result = server.get_token_price(Parameters(request)).await;
@@ -36,10 +41,15 @@ async fn test_get_token_price_success() {
 
 #[tokio::test]
 async fn test_get_token_price_default_fee() {
-    let server = create_test_server();
+    let server = create_test_server().await;
     let request = GetTokenPriceRequest {
         token: WETH_ADDRESS.to_string(),
         fee: None, // 使用默认费率
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
     };
 
     let result = server.get_token_price(Parameters(request)).await;
@@ -61,17 +71,27 @@ async fn test_get_token_price_default_fee() {
 
 #[tokio::test]
 async fn test_price_queries_consistency() {
-    let server = create_test_server();
+    let server = create_test_server().await;
 
     // 查询同一代币的价格两次，结果应该相近
     let request1 = GetTokenPriceRequest {
         token: WBTC_ADDRESS.to_string(),
         fee: Some(3000),
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
     };
 
     let request2 = GetTokenPriceRequest {
         token: WBTC_ADDRESS.to_string(),
         fee: Some(3000),
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
     };
 
     let result1 = server.get_token_price(Parameters(request1)).await;
@@ -81,4 +101,250 @@ async fn test_price_queries_consistency() {
     assert!(result2.is_ok(), "第二次价格查询应该成功");
 
     // 注意：由于区块链状态可能变化，这里只验证查询成功，不验证价格完全相等
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_get_token_price_chainlink() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WETH_ADDRESS.to_string(),
+        fee: None,
+        source: Some(PriceSource::Chainlink),
+        quote_token: None,
+        // 分叉节点的区块时间可能落后于喂价心跳，这里放宽新鲜度要求
+        max_age_secs: Some(u64::MAX),
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ Chainlink 价格查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ Chainlink 价格查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "Chainlink 价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_auto_source() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WBTC_ADDRESS.to_string(),
+        fee: None,
+        source: Some(PriceSource::Auto),
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    // auto 模式下 Chainlink 不可用时会回退到 Uniswap，查询应始终成功
+    assert!(result.is_ok(), "auto 来源的价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_in_eth() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WBTC_ADDRESS.to_string(),
+        fee: None,
+        source: None,
+        quote_token: Some("ETH".to_string()),
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 以 ETH 计价的价格查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 以 ETH 计价的价格查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "以 ETH 计价的价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_in_symbol_quote_token() {
+    let server = create_test_server().await;
+    // WBTC / DAI 没有足够深的直连池子时会经 WETH 两跳报价
+    let request = GetTokenPriceRequest {
+        token: WBTC_ADDRESS.to_string(),
+        fee: None,
+        source: None,
+        quote_token: Some("DAI".to_string()),
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+    assert!(result.is_ok(), "以 DAI 计价的价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_by_symbol() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: "wbtc".to_string(),
+        fee: None,
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 按符号查询价格成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 按符号查询价格失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "按符号查询价格应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_unknown_symbol() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: "NOT_A_TOKEN".to_string(),
+        fee: None,
+        source: None,
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+    assert!(result.is_err(), "未知符号应该报错");
+}
+
+#[tokio::test]
+async fn test_get_token_price_chainlink_rejects_non_usd_quote() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WBTC_ADDRESS.to_string(),
+        fee: None,
+        source: Some(PriceSource::Chainlink),
+        quote_token: Some("ETH".to_string()),
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+    assert!(result.is_err(), "Chainlink 来源只支持 USD 计价");
+}
+
+#[tokio::test]
+async fn test_get_token_price_spot() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WETH_ADDRESS.to_string(),
+        fee: Some(500),
+        source: Some(PriceSource::Spot),
+        quote_token: None,
+        max_age_secs: None,
+        twap_window_secs: None,
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 池子即时价格查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 池子即时价格查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "池子即时价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_token_price_twap() {
+    let server = create_test_server().await;
+    let request = GetTokenPriceRequest {
+        token: WBTC_ADDRESS.to_string(),
+        fee: None, // 自动选择流动性最大的池子
+        source: Some(PriceSource::Twap),
+        quote_token: Some("ETH".to_string()),
+        max_age_secs: None,
+        twap_window_secs: Some(600),
+        chain: None,
+    };
+
+    let result = server.get_token_price(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ TWAP 价格查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ TWAP 价格查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "TWAP 价格查询应该成功");
+}
+
+#[tokio::test]
+async fn test_get_price_consensus() {
+    let server = create_test_server().await;
+    let request = GetPriceConsensusRequest {
+        token: WETH_ADDRESS.to_string(),
+        fee: None,
+        // 分叉节点的区块时间可能落后于喂价心跳，这里放宽新鲜度要求
+        max_age_secs: Some(u64::MAX),
+        twap_window_secs: Some(600),
+        max_deviation_bps: Some(500),
+        chain: None,
+    };
+
+    let result = server.get_price_consensus(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 价格共识检查成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 价格共识检查失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    assert!(result.is_ok(), "价格共识检查应该成功");
+}
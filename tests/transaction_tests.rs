@@ -0,0 +1,91 @@
+use rmcp::handler::server::wrapper::Parameters;
+use wallet_mcp::{GetTransactionRequest, TransferRequest};
+
+mod common;
+use common::*;
+
+// Anvil 第二个默认账户
+const RECIPIENT_ADDRESS: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+#[tokio::test]
+async fn test_get_transaction_after_transfer() {
+    // 需要先广播一笔转账，只在本地 fork 上运行
+    if !fork_tests_enabled() {
+        println!("⚠️  跳过: 设置 WALLET_MCP_FORK_TESTS=1 并将 ETH_RPC_URL 指向本地 fork 后运行");
+        return;
+    }
+
+    let server = create_test_server().await;
+    let request = TransferRequest {
+        to: RECIPIENT_ADDRESS.to_string(),
+        token: "ETH".to_string(),
+        amount: 0.001,
+        dry_run: Some(false),
+        fee_speed: None,
+        chain: None,
+    };
+    let transfer = server
+        .transfer(Parameters(request))
+        .await
+        .expect("本地 fork 上的转账应该成功");
+    let transfer_text = response_text(&transfer);
+    let tx_hash = response_field(&transfer_text, "tx")
+        .expect("转账响应应包含交易哈希")
+        .to_string();
+
+    let result = server
+        .get_transaction(Parameters(GetTransactionRequest {
+            tx_hash: tx_hash.clone(),
+            chain: None,
+        }))
+        .await;
+
+    match &result {
+        Ok(response) => {
+            println!("✅ 交易查询成功");
+            println!("📄 响应内容: {:?}", response);
+        }
+        Err(error) => {
+            println!("❌ 交易查询失败");
+            println!("🚫 错误信息: {:?}", error);
+        }
+    }
+
+    let text = response_text(&result.expect("刚广播的交易应该能查到"));
+    assert_eq!(response_field(&text, "tx"), Some(tx_hash.as_str()));
+    assert_eq!(response_field(&text, "status"), Some("mined"));
+    let gas_used = response_field(&text, "gas_used").expect("已上链的交易应有 gas_used");
+    assert!(gas_used.starts_with("21000 / "), "ETH 转账应使用 21000 gas: {}", gas_used);
+    let confirmations = response_field(&text, "confirmations").expect("已上链的交易应有确认数");
+    assert!(confirmations.parse::<u64>().unwrap() >= 1);
+}
+
+#[tokio::test]
+async fn test_get_transaction_not_found() {
+    let server = create_test_server().await;
+    let request = GetTransactionRequest {
+        tx_hash: "0x0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+        chain: None,
+    };
+
+    let result = server.get_transaction(Parameters(request)).await;
+
+    match &result {
+        Ok(response) => println!("❌ 不存在的交易不应该返回结果: {:?}", response),
+        Err(error) => println!("✅ 不存在的交易被拒绝: {:?}", error),
+    }
+
+    assert!(result.is_err(), "不存在的交易应该返回错误");
+}
+
+#[tokio::test]
+async fn test_get_transaction_invalid_hash() {
+    let server = create_test_server().await;
+    let request = GetTransactionRequest {
+        tx_hash: "invalid_hash".to_string(),
+        chain: None,
+    };
+
+    let result = server.get_transaction(Parameters(request)).await;
+    assert!(result.is_err(), "无效交易哈希应该返回错误");
+}